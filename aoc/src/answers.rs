@@ -0,0 +1,96 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Result};
+use common::{Answer, Part};
+use serde::{de::Error, Deserialize, Deserializer};
+
+/// The contents of an answers file.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Answers {
+    #[serde(default)]
+    pub answer: Vec<Entry>,
+}
+
+/// The recorded answers for one input of one day. TOML integers stop at
+/// `i64`, so larger answers are written as strings.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Entry {
+    pub day: u8,
+    pub input: String,
+    #[serde(default, deserialize_with = "recorded")]
+    pub part1: Option<i128>,
+    #[serde(default, deserialize_with = "recorded")]
+    pub part2: Option<i128>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Recorded {
+    Number(i64),
+    Text(String),
+}
+
+fn recorded<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<i128>, D::Error> {
+    match Option::<Recorded>::deserialize(deserializer)? {
+        None => Ok(None),
+        Some(Recorded::Number(n)) => Ok(Some(n.into())),
+        Some(Recorded::Text(text)) => text
+            .parse()
+            .map(Some)
+            .map_err(|_| D::Error::custom(format!("invalid answer {text:?}"))),
+    }
+}
+
+impl Entry {
+    pub fn expected(&self, part: Part) -> Option<Answer> {
+        match part {
+            Part::One => self.part1,
+            Part::Two => self.part2,
+        }
+        .map(Answer::from)
+    }
+
+    /// Read the input, relative to the directory of the answers file.
+    pub fn read_input(&self, base: &Path) -> Result<String> {
+        let path = base.join(&self.input);
+        std::fs::read_to_string(&path)
+            .map_err(|e| anyhow!("Failed to read {}: {e}", path.display()))
+    }
+}
+
+/// Load an answers file, returning it with the directory inputs are relative
+/// to.
+pub fn load(path: &str) -> Result<(Answers, PathBuf)> {
+    let text = std::fs::read_to_string(path).map_err(|e| anyhow!("Failed to read {path}: {e}"))?;
+    let answers = toml::from_str(&text).map_err(|e| anyhow!("Invalid {path}: {e}"))?;
+    let base = Path::new(path)
+        .parent()
+        .unwrap_or(Path::new(""))
+        .to_path_buf();
+    Ok((answers, base))
+}
+
+#[test]
+fn test_large_answers() {
+    let answers: Answers = toml::from_str(
+        r#"
+        [[answer]]
+        day = 4
+        input = "input.txt"
+        part1 = 13
+        part2 = "170141183460469231731687303715884105727"
+        "#,
+    )
+    .unwrap();
+    let entry = &answers.answer[0];
+    assert_eq!(entry.expected(Part::One), Some(Answer::from(13)));
+    assert_eq!(entry.expected(Part::Two), Some(Answer::from(i128::MAX)));
+
+    let e = toml::from_str::<Answers>("[[answer]]\nday = 4\ninput = \"x\"\npart1 = \"lots\"\n");
+    assert!(e
+        .unwrap_err()
+        .to_string()
+        .contains("invalid answer \"lots\""));
+}
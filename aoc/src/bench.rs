@@ -0,0 +1,190 @@
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, Result};
+use common::{Part, Solution};
+use serde::Serialize;
+
+use crate::answers::{self, Entry};
+use crate::table;
+
+/// Timings of one stage over all iterations.
+#[derive(Debug, Serialize)]
+struct Timing {
+    day: u8,
+    input: String,
+    stage: String,
+    iterations: usize,
+    min_ns: u64,
+    median_ns: u64,
+    max_ns: u64,
+}
+
+struct Options<'a> {
+    iterations: usize,
+    day: Option<u8>,
+    json: bool,
+    path: &'a str,
+}
+
+fn options(args: &[String]) -> Result<Options<'_>> {
+    let mut options = Options {
+        iterations: 10,
+        day: None,
+        json: false,
+        path: "answers.toml",
+    };
+    let mut path = None;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--iterations" => {
+                let n = args.next().ok_or(anyhow!("{}", crate::USAGE))?;
+                options.iterations = n
+                    .parse()
+                    .ok()
+                    .filter(|n| *n > 0)
+                    .ok_or(anyhow!("Invalid iteration count: {n}"))?;
+            }
+            "--day" => {
+                let day = args.next().ok_or(anyhow!("{}", crate::USAGE))?;
+                options.day = Some(day.parse().map_err(|_| anyhow!("Invalid day: {day}"))?);
+            }
+            "--json" => options.json = true,
+            arg if arg.starts_with("--") || path.is_some() => {
+                return Err(anyhow!("{}", crate::USAGE))
+            }
+            arg => path = Some(arg),
+        }
+    }
+
+    if let Some(path) = path {
+        options.path = path;
+    }
+    Ok(options)
+}
+
+/// Run `f` `iterations` times and summarize how long it took.
+fn time<T>(
+    entry: &Entry,
+    stage: &str,
+    iterations: usize,
+    mut f: impl FnMut() -> Result<T>,
+) -> Result<(Timing, T)> {
+    let mut times = Vec::with_capacity(iterations);
+    let mut last = None;
+    for _ in 0..iterations {
+        let start = Instant::now();
+        let output = f()?;
+        times.push(start.elapsed());
+        last = Some(output);
+    }
+    times.sort();
+
+    let nanos = |d: Duration| d.as_nanos() as u64;
+    let timing = Timing {
+        day: entry.day,
+        input: entry.input.clone(),
+        stage: stage.to_string(),
+        iterations,
+        min_ns: nanos(times[0]),
+        median_ns: nanos(times[times.len() / 2]),
+        max_ns: nanos(times[times.len() - 1]),
+    };
+    Ok((timing, last.expect("at least one iteration")))
+}
+
+/// Time parsing and both parts of a recorded input. Parts without a recorded
+/// answer are skipped, they may not finish in reasonable time.
+fn bench_entry(
+    solution: &dyn Solution,
+    entry: &Entry,
+    input: &str,
+    iterations: usize,
+) -> Result<Vec<Timing>> {
+    let context = |e: anyhow::Error| anyhow!("Day {} on {}: {e}", entry.day, entry.input);
+
+    let (timing, model) =
+        time(entry, "parse", iterations, || solution.parse_model(input)).map_err(context)?;
+    let mut timings = vec![timing];
+
+    for part in Part::ALL {
+        if entry.expected(part).is_none() {
+            continue;
+        }
+        let stage = format!("part{part}");
+        let (timing, _) = time(entry, &stage, iterations, || {
+            solution.solve_model(model.as_ref(), part)
+        })
+        .map_err(context)?;
+        timings.push(timing);
+    }
+
+    Ok(timings)
+}
+
+fn print_table(timings: &[Timing]) {
+    let duration = |nanos: u64| format!("{:?}", Duration::from_nanos(nanos));
+    let cells: Vec<Vec<String>> = timings
+        .iter()
+        .map(|timing| {
+            vec![
+                timing.day.to_string(),
+                timing.input.clone(),
+                timing.stage.clone(),
+                duration(timing.min_ns),
+                duration(timing.median_ns),
+                duration(timing.max_ns),
+            ]
+        })
+        .collect();
+    table::print(&["Day", "Input", "Stage", "Min", "Median", "Max"], &cells);
+}
+
+pub fn bench(solutions: &[Box<dyn Solution>], args: &[String]) -> Result<()> {
+    let options = options(args)?;
+    let (answers, base) = answers::load(options.path)?;
+
+    let mut timings = Vec::new();
+    for entry in &answers.answer {
+        if options.day.is_some_and(|day| day != entry.day) {
+            continue;
+        }
+        let solution = solutions
+            .iter()
+            .find(|solution| solution.day() == entry.day)
+            .ok_or(anyhow!("No solution for day {}", entry.day))?;
+        let input = entry.read_input(&base)?;
+        timings.extend(bench_entry(
+            solution.as_ref(),
+            entry,
+            &input,
+            options.iterations,
+        )?);
+    }
+
+    if options.json {
+        println!("{}", serde_json::to_string_pretty(&timings)?);
+    } else {
+        print_table(&timings);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_bench_entry() {
+    let entry = Entry {
+        day: 9,
+        input: "dec9/src/input1.txt".to_string(),
+        part1: Some(114),
+        part2: None,
+    };
+    let input = include_str!("../../dec9/src/input1.txt");
+    let timings = bench_entry(&dec9::Dec9, &entry, input, 3).unwrap();
+
+    let stages: Vec<&str> = timings.iter().map(|t| t.stage.as_str()).collect();
+    assert_eq!(stages, ["parse", "part1"]);
+    assert!(timings
+        .iter()
+        .all(|t| t.iterations == 3 && t.min_ns <= t.median_ns && t.median_ns <= t.max_ns));
+}
@@ -0,0 +1,69 @@
+use anyhow::{anyhow, Result};
+use common::{Part, Solution};
+
+mod answers;
+mod bench;
+mod table;
+mod verify;
+
+const USAGE: &str = "Usage: aoc run <day> <part> [input-file|-]
+       aoc verify [answers-file]
+       aoc bench [--iterations <n>] [--day <day>] [--json] [answers-file]";
+
+fn solutions() -> Vec<Box<dyn Solution>> {
+    vec![
+        Box::new(dec1::Dec1),
+        Box::new(dec2::Dec2::default()),
+        Box::new(dec3::Dec3),
+        Box::new(dec4::Dec4),
+        Box::new(dec5::Dec5::default()),
+        Box::new(dec6::Dec6),
+        Box::new(dec7::Dec7::default()),
+        Box::new(dec8::Dec8),
+        Box::new(dec9::Dec9),
+    ]
+}
+
+fn solution(day: u8) -> Result<Box<dyn Solution>> {
+    solutions()
+        .into_iter()
+        .find(|solution| solution.day() == day)
+        .ok_or(anyhow!("No solution for day {day}"))
+}
+
+fn read_input(path: Option<&str>) -> Result<String> {
+    match path {
+        None | Some("-") => Ok(std::io::read_to_string(std::io::stdin())?),
+        Some(path) => {
+            std::fs::read_to_string(path).map_err(|e| anyhow!("Failed to read {path}: {e}"))
+        }
+    }
+}
+
+fn run(args: &[String]) -> Result<()> {
+    let [day, part, rest @ ..] = args else {
+        return Err(anyhow!("{USAGE}"));
+    };
+    if rest.len() > 1 {
+        return Err(anyhow!("{USAGE}"));
+    }
+
+    let day: u8 = day.parse().map_err(|_| anyhow!("Invalid day: {day}"))?;
+    let part: Part = part.parse()?;
+    let solution = solution(day)?;
+    let input = read_input(rest.first().map(String::as_str))?;
+
+    let result = solution.solve(&input, part)?;
+    println!("Result: {}", result);
+    Ok(())
+}
+
+fn main() -> Result<()> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args.split_first() {
+        Some((command, rest)) if command == "run" => run(rest),
+        Some((command, rest)) if command == "verify" => verify::verify(&solutions(), rest),
+        Some((command, rest)) if command == "bench" => bench::bench(&solutions(), rest),
+        _ => Err(anyhow!("{USAGE}")),
+    }
+}
@@ -0,0 +1,208 @@
+use std::fmt;
+use std::path::Path;
+
+use anyhow::{anyhow, Result};
+use common::{Part, Solution};
+
+use crate::answers::{self, Answers, Entry};
+use crate::table;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Status {
+    Pass,
+    Fail,
+    Missing,
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Status::Pass => write!(f, "pass"),
+            Status::Fail => write!(f, "FAIL"),
+            Status::Missing => write!(f, "missing"),
+        }
+    }
+}
+
+struct Row {
+    day: u8,
+    part: Part,
+    input: String,
+    expected: String,
+    actual: String,
+    status: Status,
+}
+
+fn check_entry(solutions: &[Box<dyn Solution>], entry: &Entry, base: &Path) -> Vec<Row> {
+    let model = solutions
+        .iter()
+        .find(|solution| solution.day() == entry.day)
+        .ok_or(anyhow!("No solution for day {}", entry.day))
+        .and_then(|solution| {
+            let input = entry.read_input(base)?;
+            Ok((solution, solution.parse_model(&input)?))
+        });
+
+    Part::ALL
+        .into_iter()
+        .map(|part| {
+            let row = |actual: String, status| Row {
+                day: entry.day,
+                part,
+                input: entry.input.clone(),
+                expected: entry
+                    .expected(part)
+                    .map(|a| a.to_string())
+                    .unwrap_or_default(),
+                actual,
+                status,
+            };
+
+            let Some(expected) = entry.expected(part) else {
+                return row(String::new(), Status::Missing);
+            };
+            let (solution, model) = match &model {
+                Ok(model) => model,
+                Err(e) => return row(e.to_string(), Status::Fail),
+            };
+            match solution.solve_model(model.as_ref(), part) {
+                Ok(actual) if actual == expected => row(actual.to_string(), Status::Pass),
+                Ok(actual) => row(actual.to_string(), Status::Fail),
+                Err(e) => row(e.to_string(), Status::Fail),
+            }
+        })
+        .collect()
+}
+
+/// Solve every recorded input. Days without any recorded input are reported
+/// as missing.
+fn check(solutions: &[Box<dyn Solution>], answers: &Answers, base: &Path) -> Vec<Row> {
+    let mut rows: Vec<Row> = answers
+        .answer
+        .iter()
+        .flat_map(|entry| check_entry(solutions, entry, base))
+        .collect();
+
+    for solution in solutions {
+        if answers
+            .answer
+            .iter()
+            .all(|entry| entry.day != solution.day())
+        {
+            rows.extend(Part::ALL.into_iter().map(|part| Row {
+                day: solution.day(),
+                part,
+                input: "-".to_string(),
+                expected: String::new(),
+                actual: String::new(),
+                status: Status::Missing,
+            }));
+        }
+    }
+
+    rows.sort_by_key(|row| row.day);
+    rows
+}
+
+fn print_table(rows: &[Row]) {
+    let cells: Vec<Vec<String>> = rows
+        .iter()
+        .map(|row| {
+            vec![
+                row.day.to_string(),
+                row.part.to_string(),
+                row.input.clone(),
+                row.expected.clone(),
+                // Errors can span several lines, only the first fits the table.
+                row.actual.lines().next().unwrap_or_default().to_string(),
+                row.status.to_string(),
+            ]
+        })
+        .collect();
+    table::print(
+        &["Day", "Part", "Input", "Expected", "Actual", "Status"],
+        &cells,
+    );
+}
+
+pub fn verify(solutions: &[Box<dyn Solution>], args: &[String]) -> Result<()> {
+    let path = match args {
+        [] => "answers.toml",
+        [path] => path.as_str(),
+        _ => return Err(anyhow!("{}", crate::USAGE)),
+    };
+    let (answers, base) = answers::load(path)?;
+
+    let rows = check(solutions, &answers, &base);
+    print_table(&rows);
+
+    let count = |status| rows.iter().filter(|row| row.status == status).count();
+    let failed = count(Status::Fail);
+    println!(
+        "\n{} passed, {failed} failed, {} missing",
+        count(Status::Pass),
+        count(Status::Missing)
+    );
+
+    if failed > 0 {
+        return Err(anyhow!("{failed} answers did not match"));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+fn statuses(text: &str) -> Vec<(u8, Part, Status)> {
+    let answers: Answers = toml::from_str(text).unwrap();
+    let solutions: Vec<Box<dyn Solution>> = vec![Box::new(dec9::Dec9), Box::new(dec6::Dec6)];
+    check(
+        &solutions,
+        &answers,
+        Path::new(env!("CARGO_MANIFEST_DIR")).parent().unwrap(),
+    )
+    .into_iter()
+    .map(|row| (row.day, row.part, row.status))
+    .collect()
+}
+
+#[test]
+fn test_check() {
+    let text = r#"
+        [[answer]]
+        day = 9
+        input = "dec9/src/input1.txt"
+        part1 = 114
+        part2 = 3
+    "#;
+    assert_eq!(
+        statuses(text),
+        vec![
+            (6, Part::One, Status::Missing),
+            (6, Part::Two, Status::Missing),
+            (9, Part::One, Status::Pass),
+            (9, Part::Two, Status::Fail),
+        ]
+    );
+}
+
+#[test]
+fn test_unreadable_input() {
+    let text = r#"
+        [[answer]]
+        day = 6
+        input = "dec6/nope.txt"
+        part1 = 1
+
+        [[answer]]
+        day = 9
+        input = "dec9/src/input1.txt"
+    "#;
+    assert_eq!(
+        statuses(text),
+        vec![
+            (6, Part::One, Status::Fail),
+            (6, Part::Two, Status::Missing),
+            (9, Part::One, Status::Missing),
+            (9, Part::Two, Status::Missing),
+        ]
+    );
+}
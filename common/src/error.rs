@@ -0,0 +1,128 @@
+use std::fmt;
+
+use nom::error::{Error, ErrorKind};
+use nom::Offset;
+
+/// A parse failure pointing into the original input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// Line of the failure, starting at 1.
+    pub line: usize,
+    /// Column of the failure in characters, starting at 1.
+    pub column: usize,
+    /// What the parser was looking for at this position.
+    pub expected: String,
+    /// The offending line with a caret under the column.
+    pub snippet: String,
+}
+
+impl ParseError {
+    /// Create an error at byte `offset` into `source`.
+    pub fn new(source: &str, offset: usize, expected: impl Into<String>) -> Self {
+        let offset = offset.min(source.len());
+        let before = &source[..offset];
+        let line_start = before.rfind('\n').map(|i| i + 1).unwrap_or(0);
+        let line_end = source[offset..]
+            .find('\n')
+            .map(|i| offset + i)
+            .unwrap_or(source.len());
+
+        let line = before.matches('\n').count() + 1;
+        let column = source[line_start..offset].chars().count() + 1;
+
+        let number = line.to_string();
+        let gutter = " ".repeat(number.len());
+        let text = source[line_start..line_end].trim_end_matches('\r');
+        let snippet = format!(
+            "{number} | {text}\n{gutter} | {}^",
+            " ".repeat(column - 1)
+        );
+
+        Self {
+            line,
+            column,
+            expected: expected.into(),
+            snippet,
+        }
+    }
+
+    /// Create an error at `fragment`, which must be a slice of `source`.
+    pub fn at(source: &str, fragment: &str, expected: impl Into<String>) -> Self {
+        Self::new(source, source.offset(fragment), expected)
+    }
+
+    /// The parser succeeded but left `remaining` unconsumed.
+    pub fn trailing(source: &str, remaining: &str) -> Self {
+        Self::at(source, remaining, "end of input")
+    }
+
+    pub fn from_error(source: &str, error: Error<&str>) -> Self {
+        Self::at(source, error.input, expected(error.code))
+    }
+
+    pub fn from_nom(source: &str, error: nom::Err<Error<&str>>) -> Self {
+        match error {
+            nom::Err::Error(e) | nom::Err::Failure(e) => Self::from_error(source, e),
+            nom::Err::Incomplete(_) => Self::new(source, source.len(), "more input"),
+        }
+    }
+}
+
+/// Describe what a failing nom parser of the given kind was looking for.
+pub fn expected(kind: ErrorKind) -> String {
+    let expected = match kind {
+        ErrorKind::Tag => "literal",
+        ErrorKind::Char => "character",
+        ErrorKind::Digit => "digit",
+        ErrorKind::Alpha => "letter",
+        ErrorKind::AlphaNumeric => "letter or digit",
+        ErrorKind::Space => "space",
+        ErrorKind::MultiSpace => "whitespace",
+        ErrorKind::CrLf => "newline",
+        ErrorKind::OneOf => "one of the allowed characters",
+        ErrorKind::NoneOf => "a character other than the excluded ones",
+        ErrorKind::TakeWhile1 => "token",
+        ErrorKind::Verify => "valid value",
+        ErrorKind::Eof => "end of input",
+        ErrorKind::Many1 | ErrorKind::SeparatedList => "at least one item",
+        kind => return kind.description().to_string(),
+    };
+    expected.to_string()
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "expected {} at line {}, column {}:",
+            self.expected, self.line, self.column
+        )?;
+        write!(f, "{}", self.snippet)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+#[test]
+fn test_position() {
+    let source = "12 34\n56 x8\n";
+    let e = ParseError::new(source, 9, "digit");
+    assert_eq!((e.line, e.column), (2, 4));
+    assert_eq!(e.snippet, "2 | 56 x8\n  |    ^");
+}
+
+#[test]
+fn test_trailing() {
+    let source = "abc\ndef";
+    let e = ParseError::trailing(source, &source[4..]);
+    assert_eq!((e.line, e.column), (2, 1));
+    assert_eq!(e.expected, "end of input");
+}
+
+#[test]
+fn test_end_of_input() {
+    let source = "abc";
+    let e = ParseError::new(source, 3, "digit");
+    assert_eq!((e.line, e.column), (1, 4));
+    assert_eq!(e.to_string(), "expected digit at line 1, column 4:\n1 | abc\n  |    ^");
+}
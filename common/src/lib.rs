@@ -0,0 +1,179 @@
+use std::any::Any;
+use std::fmt;
+use std::str::FromStr;
+
+use anyhow::{anyhow, Result};
+
+mod error;
+mod parse;
+
+pub use error::{expected, ParseError};
+pub use parse::{parse_all, Diagnose};
+
+/// The answer to one part of a puzzle. Days return different integer types,
+/// so everything is widened to a signed 128 bit value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Answer(i128);
+
+impl Answer {
+    pub fn value(&self) -> i128 {
+        self.0
+    }
+}
+
+macro_rules! answer_from {
+    ($($t:ty),*) => {
+        $(
+            impl From<$t> for Answer {
+                fn from(value: $t) -> Self {
+                    Answer(value as i128)
+                }
+            }
+        )*
+    };
+}
+
+answer_from!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize, i128);
+
+impl TryFrom<u128> for Answer {
+    type Error = anyhow::Error;
+
+    fn try_from(value: u128) -> Result<Self> {
+        Ok(Answer(
+            value
+                .try_into()
+                .map_err(|_| anyhow!("Answer {value} is too large"))?,
+        ))
+    }
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Part {
+    One,
+    Two,
+}
+
+impl Part {
+    pub const ALL: [Part; 2] = [Part::One, Part::Two];
+}
+
+impl TryFrom<u8> for Part {
+    type Error = anyhow::Error;
+
+    fn try_from(value: u8) -> Result<Self> {
+        match value {
+            1 => Ok(Part::One),
+            2 => Ok(Part::Two),
+            _ => Err(anyhow!("Invalid part: {value}")),
+        }
+    }
+}
+
+impl FromStr for Part {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        s.parse::<u8>()
+            .map_err(|_| anyhow!("Invalid part: {s}"))?
+            .try_into()
+    }
+}
+
+impl fmt::Display for Part {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Part::One => write!(f, "1"),
+            Part::Two => write!(f, "2"),
+        }
+    }
+}
+
+/// A day of the calendar. The input is parsed once into `Model`, which both
+/// parts then solve.
+pub trait Puzzle {
+    const DAY: u8;
+
+    type Model: 'static;
+
+    fn parse(&self, input: &str) -> Result<Self::Model>;
+
+    fn part1(&self, model: &Self::Model) -> Result<Answer>;
+
+    fn part2(&self, model: &Self::Model) -> Result<Answer>;
+}
+
+/// Object safe view of a `Puzzle`, so days with different models can be
+/// stored and iterated together.
+pub trait Solution {
+    fn day(&self) -> u8;
+
+    fn parse_model(&self, input: &str) -> Result<Box<dyn Any>>;
+
+    fn solve_model(&self, model: &dyn Any, part: Part) -> Result<Answer>;
+
+    fn solve(&self, input: &str, part: Part) -> Result<Answer> {
+        let model = self.parse_model(input)?;
+        self.solve_model(model.as_ref(), part)
+    }
+}
+
+impl<P: Puzzle> Solution for P {
+    fn day(&self) -> u8 {
+        P::DAY
+    }
+
+    fn parse_model(&self, input: &str) -> Result<Box<dyn Any>> {
+        Ok(Box::new(self.parse(input)?))
+    }
+
+    fn solve_model(&self, model: &dyn Any, part: Part) -> Result<Answer> {
+        let model = model
+            .downcast_ref::<P::Model>()
+            .ok_or(anyhow!("Model does not belong to day {}", P::DAY))?;
+        match part {
+            Part::One => self.part1(model),
+            Part::Two => self.part2(model),
+        }
+    }
+}
+
+#[cfg(test)]
+struct Lines;
+
+#[cfg(test)]
+impl Puzzle for Lines {
+    const DAY: u8 = 0;
+
+    type Model = Vec<String>;
+
+    fn parse(&self, input: &str) -> Result<Vec<String>> {
+        Ok(input.lines().map(String::from).collect())
+    }
+
+    fn part1(&self, model: &Vec<String>) -> Result<Answer> {
+        Ok(model.len().into())
+    }
+
+    fn part2(&self, model: &Vec<String>) -> Result<Answer> {
+        Ok((-(model.len() as i64)).into())
+    }
+}
+
+#[test]
+fn test_solution() {
+    let solutions: Vec<Box<dyn Solution>> = vec![Box::new(Lines)];
+    let r = solutions[0].solve("a\nb\nc\n", Part::Two);
+    assert_eq!(r.unwrap().to_string(), "-3");
+}
+
+#[test]
+fn test_foreign_model() {
+    let r = Lines.solve_model(&42u32, Part::One);
+    assert!(r.is_err());
+}
@@ -0,0 +1,89 @@
+use nom::character::complete::multispace0;
+use nom::combinator::all_consuming;
+use nom::error::{Error, VerboseError, VerboseErrorKind};
+use nom::sequence::terminated;
+use nom::Parser;
+
+use crate::error::{expected, ParseError};
+
+/// nom error types that can be turned into a `ParseError`.
+pub trait Diagnose<'a>: nom::error::ParseError<&'a str> {
+    fn diagnose(self, source: &'a str) -> ParseError;
+}
+
+impl<'a> Diagnose<'a> for Error<&'a str> {
+    fn diagnose(self, source: &'a str) -> ParseError {
+        ParseError::from_error(source, self)
+    }
+}
+
+/// Reports the innermost failure, qualified by the innermost `context` around
+/// it if there is one.
+impl<'a> Diagnose<'a> for VerboseError<&'a str> {
+    fn diagnose(self, source: &'a str) -> ParseError {
+        let Some((input, kind)) = self.errors.first() else {
+            return ParseError::new(source, 0, "valid input");
+        };
+
+        let what = match kind {
+            VerboseErrorKind::Char(c) => format!("{c:?}"),
+            VerboseErrorKind::Nom(kind) => expected(*kind),
+            VerboseErrorKind::Context(context) => context.to_string(),
+        };
+
+        let context = self.errors.iter().skip(1).find_map(|(_, kind)| match kind {
+            VerboseErrorKind::Context(context) => Some(context),
+            _ => None,
+        });
+
+        match context {
+            Some(context) => ParseError::at(source, input, format!("{what} in {context}")),
+            None => ParseError::at(source, input, what),
+        }
+    }
+}
+
+/// Run `parser` over all of `input`. Trailing whitespace and newlines are
+/// accepted, anything else left over is an error.
+pub fn parse_all<'a, O, E, P>(parser: P, input: &'a str) -> Result<O, ParseError>
+where
+    P: Parser<&'a str, O, E>,
+    E: Diagnose<'a>,
+{
+    let mut parser = all_consuming(terminated(parser, multispace0));
+
+    match parser(input) {
+        Ok((_, output)) => Ok(output),
+        Err(nom::Err::Error(e) | nom::Err::Failure(e)) => Err(e.diagnose(input)),
+        Err(nom::Err::Incomplete(_)) => Err(ParseError::new(input, input.len(), "more input")),
+    }
+}
+
+#[cfg(test)]
+use nom::{
+    character::complete::{newline, space1, u64 as nom_u64},
+    error::context,
+    multi::separated_list1,
+};
+
+#[test]
+fn test_trailing_whitespace() {
+    let parser = separated_list1(newline, nom_u64::<_, Error<_>>);
+    assert_eq!(parse_all(parser, "1\n2\n3\n\n  \n").unwrap(), vec![1, 2, 3]);
+}
+
+#[test]
+fn test_trailing_input() {
+    let parser = separated_list1(newline, nom_u64::<_, Error<_>>);
+    let e = parse_all(parser, "1\n2\nx\n").unwrap_err();
+    assert_eq!((e.line, e.column), (3, 1));
+    assert_eq!(e.expected, "end of input");
+}
+
+#[test]
+fn test_context() {
+    let line = context("row", separated_list1(space1, nom_u64::<_, VerboseError<_>>));
+    let e = parse_all(line, "x 2").unwrap_err();
+    assert_eq!((e.line, e.column), (1, 1));
+    assert_eq!(e.expected, "digit in row");
+}
@@ -1,34 +1,65 @@
-
 use anyhow::{anyhow, Result};
+use common::{Answer, Puzzle};
 use std::io::BufRead;
 
+pub struct Dec1;
+
+impl Puzzle for Dec1 {
+    const DAY: u8 = 1;
+
+    type Model = Vec<String>;
+
+    fn parse(&self, input: &str) -> Result<Vec<String>> {
+        Ok(input.lines().map(String::from).collect())
+    }
+
+    fn part1(&self, lines: &Vec<String>) -> Result<Answer> {
+        let mut sum = 0;
+        for line in lines {
+            sum += calibration(line)?;
+        }
+        Ok(sum.into())
+    }
+
+    fn part2(&self, lines: &Vec<String>) -> Result<Answer> {
+        let mut sum = 0;
+        for line in lines {
+            sum += calibration2(line)?;
+        }
+        Ok(sum.into())
+    }
+}
+
+fn calibration(line: &str) -> Result<u32> {
+    let first = line
+        .chars()
+        .find(|c| c.is_ascii_digit())
+        .ok_or(anyhow!("No digit on line"))?
+        .to_digit(10)
+        .ok_or(anyhow!("Failed to parse first digit"))?;
+
+    let last = line
+        .chars()
+        .rev()
+        .find(|c| c.is_ascii_digit())
+        .ok_or(anyhow!("No digit on line"))?
+        .to_digit(10)
+        .ok_or(anyhow!("Failed to parse last digit"))?;
+
+    Ok(first * 10 + last)
+}
+
 pub fn dec1(input: impl BufRead) -> Result<u32> {
     let mut sum = 0;
 
     for line in input.lines() {
-        let line = line?;
-        let first = line
-            .chars()
-            .find(|c| c.to_digit(10).is_some())
-            .ok_or(anyhow!("No digit on line"))?
-            .to_digit(10)
-            .ok_or(anyhow!("Failed to parse first digit"))?;
-
-        let last = line
-            .chars()
-            .rev()
-            .find(|c| c.to_digit(10).is_some())
-            .ok_or(anyhow!("No digit on line"))?
-            .to_digit(10)
-            .ok_or(anyhow!("Failed to parse last digit"))?;
-
-        sum += first * 10 + last;
+        sum += calibration(&line?)?;
     }
 
-    return Ok(sum);
+    Ok(sum)
 }
 
-pub fn dec1_2(input: impl BufRead) -> Result<u32> {
+fn calibration2(line: &str) -> Result<u32> {
     let mapping = vec![
         ("0", 0),
         ("1", 1),
@@ -52,42 +83,33 @@ pub fn dec1_2(input: impl BufRead) -> Result<u32> {
         ("nine", 9),
     ];
 
+    let first = mapping
+        .iter()
+        .map(|x| line.find(x.0).map(|pos| (pos, x.1)))
+        .filter(|x| x.is_some())
+        .min()
+        .map(|x| x.unwrap().1)
+        .ok_or(anyhow!("No input on line"))?;
+
+    let last = mapping
+        .iter()
+        .map(|x| line.rfind(x.0).map(|pos| (pos, x.1)))
+        .filter(|x| x.is_some())
+        .max()
+        .map(|x| x.unwrap().1)
+        .ok_or(anyhow!("No input on line"))?;
+
+    Ok(first * 10 + last)
+}
+
+pub fn dec1_2(input: impl BufRead) -> Result<u32> {
     let mut sum = 0;
 
     for line in input.lines() {
-        let line = line?;
-        let first = mapping
-            .iter()
-            .map(|x| {
-                if let Some(pos) = line.find(x.0) {
-                    Some((pos, x.1))
-                } else {
-                    None
-                }
-            })
-            .filter(|x| x.is_some())
-            .min()
-            .map(|x| x.unwrap().1)
-            .ok_or(anyhow!("No input on line"))?;
-
-        let last = mapping
-            .iter()
-            .map(|x| {
-                if let Some(pos) = line.rfind(x.0) {
-                    Some((pos, x.1))
-                } else {
-                    None
-                }
-            })
-            .filter(|x| x.is_some())
-            .max()
-            .map(|x| x.unwrap().1)
-            .ok_or(anyhow!("No input on line"))?;
-
-        sum += first * 10 + last;
+        sum += calibration2(&line?)?;
     }
 
-    return Ok(sum);
+    Ok(sum)
 }
 
 #[test]
@@ -1,25 +1,49 @@
-use anyhow::anyhow;
 use anyhow::Result;
+use common::{parse_all, Answer, ParseError, Puzzle};
 use nom::branch::alt;
-use nom::branch::permutation;
 use nom::character::complete::newline;
 use nom::combinator::map;
-use nom::combinator::opt;
-use nom::multi::many0;
 use nom::multi::separated_list1;
-use nom::sequence::terminated;
-use nom::Finish;
 use nom::{bytes::complete::tag, character::complete::digit1, sequence::tuple, IResult};
-use nom_permutation::permutation_opt;
 
 #[derive(Debug)]
-struct Draw {
+pub struct Draw {
     red: u32,
     green: u32,
     blue: u32,
 }
 
-type GameList = Vec<(u32, Vec<Draw>)>;
+pub type GameList = Vec<(u32, Vec<Draw>)>;
+
+/// The bag is the number of red, green and blue cubes that part one checks
+/// the games against.
+pub struct Dec2 {
+    pub bag: (u32, u32, u32),
+}
+
+impl Default for Dec2 {
+    fn default() -> Self {
+        Self { bag: (12, 13, 14) }
+    }
+}
+
+impl Puzzle for Dec2 {
+    const DAY: u8 = 2;
+
+    type Model = GameList;
+
+    fn parse(&self, input: &str) -> Result<GameList> {
+        Ok(parse(input)?)
+    }
+
+    fn part1(&self, games: &GameList) -> Result<Answer> {
+        Ok(possible(games, self.bag).into())
+    }
+
+    fn part2(&self, games: &GameList) -> Result<Answer> {
+        Ok(power(games).into())
+    }
+}
 
 #[derive(PartialEq)]
 enum Color {
@@ -29,14 +53,6 @@ enum Color {
 }
 
 impl Color {
-    fn tag(&self) -> &'static str {
-        match self {
-            Color::Red => " red",
-            Color::Green => " green",
-            Color::Blue => " blue",
-        }
-    }
-
     fn parse<'a>() -> impl FnMut(&'a str) -> IResult<&'a str, Self> {
         map(
             alt((tag(" red"), tag(" green"), tag(" blue"))),
@@ -104,53 +120,47 @@ fn parse_game(input: &str) -> IResult<&str, GameList> {
         |(id, game)| (id, game),
     );
 
-    terminated(separated_list1(newline, game), many0(newline))(input)
+    separated_list1(newline, game)(input)
 }
 
-pub fn dec2_1(input: &str, bag: (u32, u32, u32)) -> Result<u32> {
-    let (red, green, blue) = bag;
-    let games = match parse_game(input).finish() {
-        Ok(("", games)) => games,
-        _ => {
-            return Err(anyhow!("Failed to parse"));
-        }
-    };
+fn parse(input: &str) -> Result<GameList, ParseError> {
+    parse_all(parse_game, input)
+}
 
-    let sum: u32 = games
-        .into_iter()
+fn possible(games: &GameList, bag: (u32, u32, u32)) -> u32 {
+    let (red, green, blue) = bag;
+    games
+        .iter()
         .filter(|(_, games)| {
             games
                 .iter()
                 .all(|game| game.red <= red && game.blue <= blue && game.green <= green)
         })
         .map(|(id, _)| id)
-        .reduce(|acc, id| acc + id)
-        .unwrap();
-
-    Ok(sum)
+        .sum()
 }
 
-pub fn dec2_2(input: &str) -> Result<u32> {
-    let games = match parse_game(input).finish() {
-        Ok(("", games)) => games,
-        _ => {
-            return Err(anyhow!("Failed to parse"));
-        }
-    };
-
-    let powers: u32 = games
-        .into_iter()
+fn power(games: &GameList) -> u32 {
+    games
+        .iter()
         .map(|(_, games)| {
             let red = games.iter().map(|game| game.red).max().unwrap();
             let green = games.iter().map(|game| game.green).max().unwrap();
             let blue = games.iter().map(|game| game.blue).max().unwrap();
             (red, green, blue)
-        }).map(|(red, green, blue)| red * green * blue).reduce(|acc, power| acc + power).unwrap();
-
-
+        })
+        .map(|(red, green, blue)| red * green * blue)
+        .sum()
+}
 
+pub fn dec2_1(input: &str, bag: (u32, u32, u32)) -> Result<u32> {
+    let games = parse(input)?;
+    Ok(possible(&games, bag))
+}
 
-    Ok(powers)
+pub fn dec2_2(input: &str) -> Result<u32> {
+    let games = parse(input)?;
+    Ok(power(&games))
 }
 
 #[test]
@@ -1,12 +1,12 @@
+use anyhow::Result;
+use common::{parse_all, Answer, ParseError, Puzzle};
 use std::collections::{BTreeMap, BTreeSet};
-use anyhow::{anyhow, Result};
 use nom::{
     branch::alt,
     bytes::complete::{tag, take_while1},
     character::complete::{digit1, newline},
-    combinator::{map, opt},
+    combinator::map,
     multi::{many1, separated_list1},
-    sequence::tuple,
     IResult,
 };
 use nom_locate::LocatedSpan;
@@ -81,6 +81,31 @@ enum Element {
     Number(Position, usize, u32),
 }
 
+pub struct Schematic {
+    elements: Vec<Vec<Element>>,
+}
+
+pub struct Dec3;
+
+impl Puzzle for Dec3 {
+    const DAY: u8 = 3;
+
+    type Model = Schematic;
+
+    fn parse(&self, input: &str) -> Result<Schematic> {
+        let elements = parse(input)?;
+        Ok(Schematic { elements })
+    }
+
+    fn part1(&self, schematic: &Schematic) -> Result<Answer> {
+        Ok(part_numbers(&schematic.elements).into())
+    }
+
+    fn part2(&self, schematic: &Schematic) -> Result<Answer> {
+        Ok(gear_ratios(&schematic.elements).into())
+    }
+}
+
 fn dot<'a>(input: Span<'a>) -> IResult<Span<'a>, Element> {
     map(tag("."), |_| Element::Discard)(input)
 }
@@ -114,19 +139,17 @@ fn line<'a>(input: Span<'a>) -> IResult<Span<'a>, Vec<Element>> {
     many1(alt((dot, part_number, gear, symbol)))(input)
 }
 
-fn parse<'a>(input: Span<'a>) -> Result<Vec<Vec<Element>>> {
-    match tuple((separated_list1(newline, line), opt(newline)))(input) {
-        Ok((input, elements)) if input.fragment() == &"" => Ok(elements.0),
-        Ok((input, _elements)) => {
-            println!("{input:?}");
-            Err(anyhow!("Failed to parse"))
-        }
-        Err(e) => {
-            println!("Failed to parse");
-            println!("{e:?}");
-            Err(anyhow!("Failed to parse"))
-        }
-    }
+/// The grid parsers track positions on a `Span`. `parse_all` works on plain
+/// `&str`, so the whole grid is parsed from a `Span` over all of it and the
+/// positions are mapped back.
+fn grid(input: &str) -> IResult<&str, Vec<Vec<Element>>> {
+    separated_list1(newline, line)(Span::new(input))
+        .map(|(remaining, elements)| (*remaining.fragment(), elements))
+        .map_err(|e| e.map_input(|span| *span.fragment()))
+}
+
+fn parse(input: &str) -> Result<Vec<Vec<Element>>, ParseError> {
+    parse_all(grid, input)
 }
 
 fn get_locations(pos: Position) -> Vec<Position> {
@@ -161,10 +184,7 @@ fn get_locations(pos: Position) -> Vec<Position> {
     positions
 }
 
-pub fn dec3_1(input: &str) -> Result<u32> {
-    let input = Span::new(input);
-
-    let elements = parse(input)?;
+fn part_numbers(elements: &[Vec<Element>]) -> u32 {
     let mut tree = BTreeMap::new();
     let mut symbols = Vec::new();
 
@@ -176,16 +196,16 @@ pub fn dec3_1(input: &str) -> Result<u32> {
                         line: pos.line,
                         column: pos.column,
                     };
-                    for i in 0..len {
+                    for i in 0..*len {
 
                         let pos_i = Position {
                             line: pos.line,
                             column: pos.column + i,
                         };
-                        tree.insert(pos_i, (pos0.clone(), num));
+                        tree.insert(pos_i, (pos0.clone(), *num));
                     }
                 }
-                Element::Symbol(pos)|Element::Gear(pos) => symbols.push(pos),
+                Element::Symbol(pos) | Element::Gear(pos) => symbols.push(pos.clone()),
                 _ => (),
             }
         }
@@ -201,14 +221,10 @@ pub fn dec3_1(input: &str) -> Result<u32> {
     }
 
     let numbers = numbers.into_iter().collect::<BTreeSet<_>>().into_iter().map(|x| x.1).collect::<Vec<_>>();
-
-    Ok(numbers.into_iter().reduce(|acc, v| acc + v).unwrap_or(0))
+    numbers.into_iter().sum()
 }
 
-pub fn dec3_2(input: &str) -> Result<u32> {
-    let input = Span::new(input);
-
-    let elements = parse(input)?;
+fn gear_ratios(elements: &[Vec<Element>]) -> u32 {
     let mut tree = BTreeMap::new();
     let mut symbols = Vec::new();
 
@@ -220,16 +236,16 @@ pub fn dec3_2(input: &str) -> Result<u32> {
                         line: pos.line,
                         column: pos.column,
                     };
-                    for i in 0..len {
+                    for i in 0..*len {
 
                         let pos_i = Position {
                             line: pos.line,
                             column: pos.column + i,
                         };
-                        tree.insert(pos_i, (pos0.clone(), num));
+                        tree.insert(pos_i, (pos0.clone(), *num));
                     }
                 }
-                Element::Gear(pos) => symbols.push(pos),
+                Element::Gear(pos) => symbols.push(pos.clone()),
                 _ => (),
             }
         }
@@ -249,8 +265,17 @@ pub fn dec3_2(input: &str) -> Result<u32> {
         }
     }
 
+    numbers.into_iter().sum()
+}
 
-    Ok(numbers.into_iter().reduce(|acc, v| acc + v).unwrap_or(0))
+pub fn dec3_1(input: &str) -> Result<u32> {
+    let elements = parse(input)?;
+    Ok(part_numbers(&elements))
+}
+
+pub fn dec3_2(input: &str) -> Result<u32> {
+    let elements = parse(input)?;
+    Ok(gear_ratios(&elements))
 }
 
 #[test]
@@ -281,3 +306,13 @@ fn test_part_2() {
     let r = dec3_2(s);
     assert_eq!(r.unwrap(), 78826761);
 }
+
+#[test]
+fn test_trailing_newlines() {
+    let grid = parse("467..\n...*.\n\n\n").unwrap();
+    assert_eq!(grid.len(), 2);
+    let gear = Position { line: 2, column: 4 };
+    assert!(grid[1]
+        .iter()
+        .any(|element| matches!(element, Element::Gear(at) if *at == gear)));
+}
@@ -0,0 +1,27 @@
+use anyhow::Result;
+use common::Puzzle;
+use dec4::{explain, Dec4};
+
+/// Print what every card read from stdin is worth: its matching numbers, the
+/// points they earn and how many copies of it are held at the end.
+fn main() -> Result<()> {
+    let input = std::io::read_to_string(std::io::stdin())?;
+    let cards = Dec4.parse(&input)?;
+
+    println!(
+        "{:>6}  {:>7}  {:>6}  {:>10}  matching",
+        "card", "matches", "points", "copies"
+    );
+    for card in explain(&cards)? {
+        let matching: Vec<_> = card.matching.iter().map(u8::to_string).collect();
+        println!(
+            "{:>6}  {:>7}  {:>6}  {:>10}  {}",
+            card.id,
+            card.matching.len(),
+            card.points,
+            card.copies,
+            matching.join(" ")
+        );
+    }
+    Ok(())
+}
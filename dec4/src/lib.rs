@@ -1,86 +1,199 @@
-use anyhow::anyhow;
-use anyhow::Result;
+use std::cell::Cell;
+
+use anyhow::{anyhow, Result};
+use common::{parse_all, Answer, ParseError, Puzzle};
 use nom::{
     bytes::complete::tag,
     character::complete::{digit1, newline, space0, space1},
-    combinator::{map, opt},
-    error::Error,
+    combinator::{cut, map, map_res, peek, verify},
+    error::{context, VerboseError},
     multi::separated_list1,
-    sequence::{delimited, preceded, terminated, tuple},
+    sequence::{delimited, preceded, tuple},
     IResult,
 };
 
-type Cards = Vec<(Vec<u64>, Vec<u64>)>;
+/// A scratchcard. Its numbers are kept as bitsets, bit `n` is set for the
+/// number `n`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Card {
+    id: u64,
+    winning: u128,
+    drawn: u128,
+    matches: u32,
+}
+
+impl Card {
+    fn new(id: u64, winning: u128, drawn: u128) -> Self {
+        Self {
+            id,
+            winning,
+            drawn,
+            matches: (winning & drawn).count_ones(),
+        }
+    }
+
+    /// The number from the `Card N:` header.
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// How many drawn numbers are winning numbers.
+    pub fn matches(&self) -> u32 {
+        self.matches
+    }
+
+    /// The drawn numbers that are winning numbers, in increasing order.
+    pub fn matching(&self) -> Vec<u8> {
+        let set = self.winning & self.drawn;
+        (0..128).filter(|n| set & 1 << n != 0).collect()
+    }
 
-fn numbers(input: &str) -> IResult<&str, Vec<u64>> {
-    separated_list1(space1, map(digit1, |s: &str| s.parse::<u64>().unwrap()))(input)
+    /// With at most 128 matches, this always fits.
+    pub fn points(&self) -> u128 {
+        match self.matches {
+            0 => 0,
+            n => 1 << (n - 1),
+        }
+    }
 }
 
-fn values(input: &str) -> IResult<&str, Vec<u64>> {
+pub type Cards = Vec<Card>;
+
+pub struct Dec4;
+
+impl Puzzle for Dec4 {
+    const DAY: u8 = 4;
+
+    type Model = Cards;
+
+    fn parse(&self, input: &str) -> Result<Cards> {
+        Ok(parse(input)?)
+    }
+
+    fn part1(&self, cards: &Cards) -> Result<Answer> {
+        points(cards)?.try_into()
+    }
+
+    fn part2(&self, cards: &Cards) -> Result<Answer> {
+        copies(cards)?.try_into()
+    }
+}
+
+/// A list of distinct numbers below 128, as a bitset.
+fn numbers(input: &str) -> IResult<&str, u128, VerboseError<&str>> {
+    let set = Cell::new(0u128);
+    // Once there are digits, a value out of range or a repeat is an error
+    // rather than the end of the list.
+    let number = preceded(
+        peek(digit1),
+        cut(context(
+            "number not listed before",
+            verify(
+                context(
+                    "number below 128",
+                    verify(map_res(digit1, str::parse::<u64>), |n| *n < 128),
+                ),
+                |n| set.get() & 1 << n == 0,
+            ),
+        )),
+    );
+    let number = map(number, |n| set.set(set.get() | 1 << n));
+    let (rest, _) = separated_list1(space1, number)(input)?;
+    Ok((rest, set.get()))
+}
+
+fn values(input: &str) -> IResult<&str, u128, VerboseError<&str>> {
     delimited(space1, numbers, space0)(input)
 }
 
-fn parse(input: &str) -> Result<Cards> {
+fn parse(input: &str) -> Result<Cards, ParseError> {
+    // Checked with `verify` rather than `map_res`, which would describe the
+    // failure as "Map on Result".
+    let id = map(
+        preceded(
+            peek(digit1),
+            cut(context(
+                "card id",
+                verify(digit1, |d: &str| d.parse::<u64>().is_ok()),
+            )),
+        ),
+        |d: &str| d.parse::<u64>().unwrap(),
+    );
     let header = map(
-        tuple((tag::<_, _, Error<_>>("Card"), space1, digit1, tag(":"))),
-        |(_, _, d, _): (_, _, &str, _)| d.parse::<u64>().unwrap(),
+        tuple((tag("Card"), space1, id, tag(":"))),
+        |(_, _, id, _)| id,
+    );
+    let line = tuple((values, tag("|"), values));
+    let full_line = context(
+        "card",
+        map(tuple((header, line)), |(id, (winning, _, drawn))| {
+            Card::new(id, winning, drawn)
+        }),
     );
-    let line = map(tuple((values, tag("|"), values)), |(a, _, c)| (a, c));
-    let full_line = preceded(header, line);
-    let mut cards = terminated(separated_list1(newline, full_line), opt(newline));
-    match cards(input) {
-        Ok((remaining, cards)) if remaining == "" => Ok(cards),
-        Ok((remain,_)) => {
-            println!("Remaining input:");
-            println!("{remain:?}");
-            Err(anyhow!("Failed to parse"))
+    parse_all(separated_list1(newline, full_line), input)
+}
+
+fn points(cards: &Cards) -> Result<u128> {
+    cards
+        .iter()
+        .try_fold(0u128, |total, card| total.checked_add(card.points()))
+        .ok_or_else(|| anyhow!("Too many points in total"))
+}
+
+/// Copies only ever go to later cards, so one pass in order settles how many
+/// of each card there are. Wins past the last card are dropped.
+fn counts(cards: &Cards) -> Result<Vec<u128>> {
+    let mut counts = vec![1u128; cards.len()];
+    for (i, card) in cards.iter().enumerate() {
+        let last = (i + card.matches() as usize).min(cards.len() - 1);
+        for j in i + 1..=last {
+            counts[j] = counts[j]
+                .checked_add(counts[i])
+                .ok_or_else(|| anyhow!("Too many copies of card {}", cards[j].id()))?;
         }
-        Err(_) => Err(anyhow!("Failed to parse")),
     }
+    Ok(counts)
 }
 
-pub fn solve1(input: &str) -> Result<u64> {
-    let cards = parse(input)?;
-    let result = cards
+fn copies(cards: &Cards) -> Result<u128> {
+    counts(cards)?
         .into_iter()
-        .map(|(win, draw)| {
-            let count: u32 = draw
-                .into_iter()
-                .filter(|number| win.contains(number))
-                .count()
-                .try_into()
-                .unwrap();
-            if count > 0 {
-                2u64.pow(count - 1)
-            } else {
-                0
-            }
-        })
-        .reduce(|acc, v| acc + v)
-        .unwrap_or(0);
+        .try_fold(0u128, |total, count| total.checked_add(count))
+        .ok_or_else(|| anyhow!("Too many cards in total"))
+}
 
-    Ok(result)
+/// What a single card contributed to both parts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Breakdown {
+    pub id: u64,
+    pub matching: Vec<u8>,
+    pub points: u128,
+    pub copies: u128,
 }
 
-pub fn solve2(input: &str) -> Result<usize> {
+/// Per card: the matching numbers, the points they earn and how many copies
+/// of the card end up held.
+pub fn explain(cards: &Cards) -> Result<Vec<Breakdown>> {
+    Ok(cards
+        .iter()
+        .zip(counts(cards)?)
+        .map(|(card, copies)| Breakdown {
+            id: card.id(),
+            matching: card.matching(),
+            points: card.points(),
+            copies,
+        })
+        .collect())
+}
+
+pub fn solve1(input: &str) -> Result<u128> {
     let cards = parse(input)?;
-    let mut stack = (0..cards.len()).collect::<Vec<_>>();
-    let mut count = 0;
-    while !stack.is_empty() {
-        let mut next = Vec::new();
-        for i in &mut stack.drain(..) {
-            count += 1;
-            let (win, draw) = &cards[i];
-            let wins = draw
-                .into_iter()
-                .filter(|number| win.contains(number))
-                .count();
-            next.append(&mut (i + 1..i + wins + 1).collect::<Vec<_>>());
-        }
-        stack.append(&mut next);
-    }
+    points(&cards)
+}
 
-    Ok(count)
+pub fn solve2(input: &str) -> Result<u128> {
+    let cards = parse(input)?;
+    copies(&cards)
 }
 
 #[test]
@@ -110,3 +223,81 @@ fn test_part_2() {
     let r = solve2(s);
     assert_eq!(r.unwrap(), 9881048);
 }
+
+#[test]
+fn test_parse_error() {
+    let s = "Card 1: 41 48 | 83 86\nCard 2: 13 x2 | 61 30\n";
+    let e = parse(s).unwrap_err();
+    assert_eq!((e.line, e.column), (2, 1));
+}
+
+#[test]
+fn test_card() {
+    let cards = parse("Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53").unwrap();
+    assert_eq!((cards[0].matches(), cards[0].points()), (4, 8));
+
+    let e = parse("Card 1: 41 128 | 83 86").unwrap_err();
+    assert_eq!((e.line, e.column), (1, 12));
+    assert_eq!(e.expected, "valid value in number below 128");
+}
+
+#[test]
+fn test_explain() {
+    let cards = parse(include_str!("input1.txt")).unwrap();
+    let breakdown = explain(&cards).unwrap();
+    assert_eq!(
+        breakdown[0],
+        Breakdown {
+            id: 1,
+            matching: vec![17, 48, 83, 86],
+            points: 8,
+            copies: 1,
+        }
+    );
+    let copies: Vec<_> = breakdown.iter().map(|card| card.copies).collect();
+    assert_eq!(copies, [1, 2, 4, 8, 14, 1]);
+}
+
+#[test]
+fn test_many_cards() {
+    // Every card wins the next `wins` cards.
+    let cards = |n: usize, wins: usize| -> String {
+        let win: Vec<_> = (1..=wins).map(|w| w.to_string()).collect();
+        (1..=n)
+            .map(|i| format!("Card {i}: {} | {} 99\n", win.join(" "), win.join(" ")))
+            .collect()
+    };
+
+    assert_eq!(solve2(&cards(5000, 1)).unwrap(), 5000 * 5001 / 2);
+    let e = solve2(&cards(200, 2)).unwrap_err();
+    assert!(e.to_string().starts_with("Too many copies of card"));
+}
+
+#[test]
+fn test_many_matches() {
+    let numbers: Vec<_> = (0..128).map(|n| n.to_string()).collect();
+    let card = format!("Card 1: {} | {}\n", numbers.join(" "), numbers.join(" "));
+    assert_eq!(solve1(&card).unwrap(), 1 << 127);
+    let e = solve1(&card.repeat(2)).unwrap_err();
+    assert_eq!(e.to_string(), "Too many points in total");
+}
+
+#[test]
+fn test_repeated_number() {
+    let e = parse("Card 1: 41 48 | 41 41 48").unwrap_err();
+    assert_eq!((e.line, e.column), (1, 20));
+    assert_eq!(e.expected, "valid value in number not listed before");
+
+    let e = parse("Card 1: 41 41 | 41 48").unwrap_err();
+    assert_eq!((e.line, e.column), (1, 12));
+}
+
+#[test]
+fn test_card_id() {
+    let cards = parse("Card 12: 1 | 1").unwrap();
+    assert_eq!(explain(&cards).unwrap()[0].id, 12);
+
+    let e = parse("Card 1: 1 | 1\nCard 99999999999999999999: 1 | 1").unwrap_err();
+    assert_eq!((e.line, e.column), (2, 6));
+    assert_eq!(e.expected, "valid value in card id");
+}
@@ -0,0 +1,136 @@
+use std::collections::BTreeMap;
+
+use anyhow::{anyhow, Result};
+
+/// The categories of an almanac, linked by the maps converting between them.
+pub(crate) struct Categories<'a> {
+    /// Outgoing maps of each category, as the target and the index of the map.
+    edges: BTreeMap<&'a str, Vec<(&'a str, usize)>>,
+}
+
+impl<'a> Categories<'a> {
+    /// Link the categories of `(from, to)` maps, given in almanac order. Maps
+    /// may come in any order and a category may map to several others, but
+    /// no category may be converted back into itself.
+    pub(crate) fn new(maps: impl IntoIterator<Item = (&'a str, &'a str)>) -> Result<Self> {
+        let mut edges: BTreeMap<&str, Vec<(&str, usize)>> = BTreeMap::new();
+        for (index, (from, to)) in maps.into_iter().enumerate() {
+            edges.entry(from).or_default().push((to, index));
+            edges.entry(to).or_default();
+        }
+
+        let categories = Self { edges };
+        if let Some(cycle) = categories.cycle() {
+            return Err(anyhow!("Maps form a cycle: {}", cycle.join(" -> ")));
+        }
+        Ok(categories)
+    }
+
+    /// A cycle of categories, starting and ending at the same one.
+    fn cycle(&self) -> Option<Vec<&'a str>> {
+        fn visit<'a>(
+            categories: &Categories<'a>,
+            name: &'a str,
+            stack: &mut Vec<&'a str>,
+            done: &mut Vec<&'a str>,
+        ) -> Option<Vec<&'a str>> {
+            if let Some(position) = stack.iter().position(|n| *n == name) {
+                let mut cycle = stack[position..].to_vec();
+                cycle.push(name);
+                return Some(cycle);
+            }
+            if done.contains(&name) {
+                return None;
+            }
+
+            stack.push(name);
+            for (to, _) in &categories.edges[name] {
+                if let Some(cycle) = visit(categories, to, stack, done) {
+                    return Some(cycle);
+                }
+            }
+            stack.pop();
+            done.push(name);
+            None
+        }
+
+        let mut done = Vec::new();
+        self.edges
+            .keys()
+            .find_map(|name| visit(self, name, &mut Vec::new(), &mut done))
+    }
+
+    /// Number of chains of maps from `name` to `to`, capped at two since more
+    /// than one is already ambiguous.
+    fn chains(&self, name: &str, to: &str, memo: &mut BTreeMap<&'a str, usize>) -> usize {
+        if name == to {
+            return 1;
+        }
+        let (&key, edges) = self.edges.get_key_value(name).expect("known category");
+        if let Some(count) = memo.get(key) {
+            return *count;
+        }
+        let count = edges
+            .iter()
+            .map(|(next, _)| self.chains(next, to, memo))
+            .sum::<usize>()
+            .min(2);
+        memo.insert(key, count);
+        count
+    }
+
+    /// The indices of the maps that convert `from` into `to`, in the order
+    /// they apply.
+    pub(crate) fn path(&self, from: &str, to: &str) -> Result<Vec<usize>> {
+        for name in [from, to] {
+            if !self.edges.contains_key(name) {
+                return Err(anyhow!("Unknown category {name}"));
+            }
+        }
+
+        let mut memo = BTreeMap::new();
+        match self.chains(from, to, &mut memo) {
+            0 => return Err(anyhow!("No maps lead from {from} to {to}")),
+            1 => (),
+            _ => return Err(anyhow!("Several chains of maps lead from {from} to {to}")),
+        }
+
+        let mut path = Vec::new();
+        let mut name = from;
+        while name != to {
+            let (next, index) = self.edges[name]
+                .iter()
+                .find(|(next, _)| self.chains(next, to, &mut memo) > 0)
+                .expect("a chain exists");
+            path.push(*index);
+            name = next;
+        }
+        Ok(path)
+    }
+}
+
+#[test]
+fn test_path() {
+    let maps = [("b", "c"), ("a", "b"), ("b", "d"), ("c", "e")];
+    let categories = Categories::new(maps).unwrap();
+    assert_eq!(categories.path("a", "e").unwrap(), vec![1, 0, 3]);
+    assert_eq!(categories.path("b", "d").unwrap(), vec![2]);
+    assert_eq!(categories.path("c", "c").unwrap(), vec![]);
+
+    let e = categories.path("d", "a").unwrap_err();
+    assert_eq!(e.to_string(), "No maps lead from d to a");
+    let e = categories.path("a", "x").unwrap_err();
+    assert_eq!(e.to_string(), "Unknown category x");
+}
+
+#[test]
+fn test_invalid() {
+    let e = Categories::new([("a", "b"), ("b", "c"), ("c", "b")])
+        .err()
+        .unwrap();
+    assert_eq!(e.to_string(), "Maps form a cycle: b -> c -> b");
+
+    let categories = Categories::new([("a", "b"), ("a", "c"), ("b", "d"), ("c", "d")]).unwrap();
+    let e = categories.path("a", "d").unwrap_err();
+    assert_eq!(e.to_string(), "Several chains of maps lead from a to d");
+}
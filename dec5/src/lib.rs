@@ -1,79 +1,115 @@
 use anyhow::anyhow;
 use anyhow::Result;
+use common::{parse_all, Answer, ParseError, Puzzle};
 use nom::character::complete::u64 as nom_u64;
 use nom::sequence::delimited;
+use nom::Offset;
 use nom::{
     bytes::complete::tag,
-    character::complete::{alpha1, multispace0, multispace1, newline, space1},
-    combinator::map,
+    character::complete::{alpha1, multispace1, newline, space1},
+    combinator::{consumed, map},
+    error::{context, VerboseError},
     multi::separated_list1,
     sequence::{pair, separated_pair, terminated, tuple},
 };
-use rust_lapper::Interval;
-use rust_lapper::Lapper;
-use std::collections::BTreeMap;
+mod categories;
+mod piecewise;
+mod validate;
 
-fn parse(input: &str) -> Result<(Vec<u64>, Vec<((&str, &str), Vec<(u64, u64, u64)>)>)> {
-    let number_list = separated_list1(space1::<_, nom::error::Error<_>>, nom_u64);
-    let seeds = delimited(tag("seeds: "), number_list, multispace1);
+use categories::Categories;
+pub use piecewise::{Piecewise, Segment};
+pub use validate::Issue;
 
-    let map_name = separated_pair(alpha1, tag("-to-"), alpha1);
-    let map_header = terminated(map_name, tag(" map:"));
-    let map_line = map(
-        tuple((nom_u64, space1, nom_u64, space1, nom_u64)),
-        |(a, _, b, _, c)| (a, b, c),
-    );
-    let map_lines = separated_list1(newline, map_line);
-    let full_map = tuple((terminated(map_header, newline), map_lines));
-    let all_maps = separated_list1(multispace1, full_map);
+type Maps = Vec<((String, String), Vec<Mapping>)>;
 
-    let mut full_input = terminated(pair(seeds, all_maps), multispace0);
+pub struct Almanac {
+    seeds: Vec<u64>,
+    maps: Maps,
+}
 
-    match full_input(input) {
-        Ok((remaining, input)) if remaining == "" => Ok(input),
-        Ok((input, _)) => {
-            println!("Remaining input:");
-            println!("{input:?}");
-            Err(anyhow!("Failed to parse"))
-        }
-        Err(e) => {
-            println!("Failed to parse");
-            println!("{e:?}");
-            Err(anyhow!("Failed to parse"))
+/// What to do with an almanac that fails validation.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Mode {
+    /// Reject the almanac.
+    Strict,
+    /// Accept the almanac. Where source ranges overlap the line listed first
+    /// wins, and ranges running past `u64::MAX` are cut short there, keeping
+    /// `u64::MAX` itself.
+    #[default]
+    Lenient,
+}
+
+#[derive(Default)]
+pub struct Dec5 {
+    pub mode: Mode,
+}
+
+impl Puzzle for Dec5 {
+    const DAY: u8 = 5;
+
+    type Model = Almanac;
+
+    fn parse(&self, input: &str) -> Result<Almanac> {
+        let almanac = parse(input)?;
+        if self.mode == Mode::Strict {
+            let issues = almanac.validate();
+            if !issues.is_empty() {
+                let issues: Vec<_> = issues.iter().map(Issue::to_string).collect();
+                return Err(anyhow!("Invalid almanac:\n{}", issues.join("\n")));
+            }
         }
+        Ok(almanac)
+    }
+
+    fn part1(&self, almanac: &Almanac) -> Result<Answer> {
+        Ok(lowest_location(&almanac.seeds, &almanac.maps)?.into())
+    }
+
+    fn part2(&self, almanac: &Almanac) -> Result<Answer> {
+        let seeds = seed_ranges(&almanac.seeds)?;
+        Ok(lowest_location_ranges(&seeds, &almanac.maps)?.into())
     }
 }
 
-fn parse2(input: &str) -> Result<(Vec<(u64, u64)>, Vec<((&str, &str), Vec<(u64, u64, u64)>)>)> {
-    let number_pair = separated_pair(nom_u64, space1::<_, nom::error::Error<_>>, nom_u64);
-    let number_list = separated_list1(space1, number_pair);
-    let seeds = delimited(tag("seeds: "), number_list, multispace1);
+fn parse(input: &str) -> Result<Almanac, ParseError> {
+    let number_list = separated_list1(space1::<_, VerboseError<_>>, nom_u64);
+    let seeds = context("seeds", delimited(tag("seeds: "), number_list, multispace1));
 
     let map_name = separated_pair(alpha1, tag("-to-"), alpha1);
     let map_header = terminated(map_name, tag(" map:"));
     let map_line = map(
-        tuple((nom_u64, space1, nom_u64, space1, nom_u64)),
-        |(a, _, b, _, c)| (a, b, c),
+        consumed(tuple((nom_u64, space1, nom_u64, space1, nom_u64))),
+        |(line, (a, _, b, _, c))| (line, (a, b, c)),
     );
     let map_lines = separated_list1(newline, map_line);
-    let full_map = tuple((terminated(map_header, newline), map_lines));
+    let full_map = context("map", tuple((terminated(map_header, newline), map_lines)));
     let all_maps = separated_list1(multispace1, full_map);
 
-    let mut full_input = terminated(pair(seeds, all_maps), multispace0);
+    let (seeds, maps) = parse_all(pair(seeds, all_maps), input)?;
 
-    match full_input(input) {
-        Ok((remaining, input)) if remaining == "" => Ok(input),
-        Ok((input, _)) => {
-            println!("Remaining input:");
-            println!("{input:?}");
-            Err(anyhow!("Failed to parse"))
-        }
-        Err(e) => {
-            println!("Failed to parse");
-            println!("{e:?}");
-            Err(anyhow!("Failed to parse"))
-        }
+    let line_number = |line: &str| input[..input.offset(line)].matches('\n').count() + 1;
+    let maps = maps
+        .into_iter()
+        .map(|((from, to), mappings)| {
+            let mappings = mappings
+                .into_iter()
+                .map(|(line, value)| Mapping::new(value, line_number(line)))
+                .collect();
+            ((from.into(), to.into()), mappings)
+        })
+        .collect();
+
+    Ok(Almanac { seeds, maps })
+}
+
+/// Seeds are given as pairs of start and length in part two.
+fn seed_ranges(seeds: &[u64]) -> Result<Vec<(u64, u64)>> {
+    let pairs = seeds.chunks_exact(2);
+    if !pairs.remainder().is_empty() {
+        return Err(anyhow!("Seed ranges must come in pairs"));
     }
+
+    Ok(pairs.map(|pair| (pair[0], pair[1])).collect())
 }
 
 #[derive(Clone, PartialEq, Eq)]
@@ -81,145 +117,145 @@ struct Mapping {
     from: u64,
     to: u64,
     length: u64,
+    /// Line of the almanac the mapping is on.
+    line: usize,
 }
 
 impl Mapping {
-    fn new(value: (u64, u64, u64)) -> Self {
+    fn new(value: (u64, u64, u64), line: usize) -> Self {
         Self {
             from: value.1,
             to: value.0,
             length: value.2,
+            line,
         }
     }
 
-    fn offset(&self) -> i64 {
-        self.to as i64 - self.from as i64
+    fn source(&self) -> (u128, u128) {
+        (self.from as u128, self.from as u128 + self.length as u128)
     }
-}
-
-pub fn solve1(input: &str) -> Result<u64> {
-    let (seeds, maps) = parse(input)?;
-    let mut trees: BTreeMap<String, _> = BTreeMap::new();
-    let mut pairs: BTreeMap<String, String> = BTreeMap::new();
-
-    for ((from, to), mappings) in maps {
-        pairs.insert(from.into(), to.into());
-        let mut range_tree: Lapper<_, Mapping> = Lapper::new(Vec::new());
-
-        for mapping in mappings {
-            let mapping: Mapping = Mapping::new(mapping);
-            let interval = Interval {
-                start: mapping.from,
-                stop: mapping.from + mapping.length,
-                val: mapping,
-            };
-            range_tree.insert(interval);
-        }
 
-        trees.insert(from.into(), range_tree);
+    fn destination(&self) -> (u128, u128) {
+        (self.to as u128, self.to as u128 + self.length as u128)
     }
 
-    let mut work: Vec<(String, u64)> = seeds
-        .into_iter()
-        .map(|seed| ("seed".into(), seed))
-        .collect();
-
-    let mut locations = Vec::new();
-
-    while !work.is_empty() {
-        let mut new_work = Vec::new();
-        for (name, value) in work.drain(..) {
-            if name == "location" {
-                locations.push(value);
-            } else {
-                let target = pairs.get(&name).ok_or(anyhow!("Could not map"))?;
-                if let Some(range) = trees
-                    .get(&name)
-                    .ok_or(anyhow!("Failed to find map for {name}"))?
-                    .find(value, value + 1)
-                    .next()
-                {
-                    let mapping = &range.val;
-                    let new_value = ((value as i64) + mapping.offset()) as u64;
-                    new_work.push((target.clone(), new_value));
-                } else {
-                    new_work.push((target.clone(), value));
-                }
-            }
-        }
-        work.append(&mut new_work);
+    /// The source range and the offset onto the destination.
+    fn range(&self) -> (u128, u128, i128) {
+        let (start, end) = self.source();
+        (start, end, self.to as i128 - self.from as i128)
     }
+}
 
-    let result = locations.into_iter().min().unwrap_or(0);
-
-    Ok(result)
+fn layer(mappings: &[Mapping]) -> Piecewise {
+    Piecewise::from_ranges(mappings.iter().map(Mapping::range))
 }
 
-/// TODO: This should probably be optimized to process ranges instead of
-/// individual points. It takes forever to run.
-pub fn solve2(input: &str) -> Result<u64> {
-    let (seeds, maps) = parse2(input)?;
-    let mut trees: BTreeMap<String, Lapper<u64, Mapping>> = BTreeMap::new();
-    let mut pairs: BTreeMap<String, String> = BTreeMap::new();
-
-    for ((from, to), mappings) in maps {
-        pairs.insert(from.into(), to.into());
-        let mut range_tree: Lapper<_, Mapping> = Lapper::new(Vec::new());
-
-        for mapping in mappings {
-            let mapping: Mapping = Mapping::new(mapping);
-            let interval = Interval {
-                start: mapping.from,
-                stop: mapping.from + mapping.length,
-                val: mapping,
-            };
-            range_tree.insert(interval);
-        }
+/// Compose the maps that convert `from` into `to` into a single function.
+fn compose(maps: &Maps, from: &str, to: &str) -> Result<Piecewise> {
+    let categories = Categories::new(
+        maps.iter()
+            .map(|((from, to), _)| (from.as_str(), to.as_str())),
+    )?;
+
+    Ok(categories
+        .path(from, to)?
+        .into_iter()
+        .fold(Piecewise::identity(), |composed, index| {
+            composed.then(&layer(&maps[index].1))
+        }))
+}
 
-        trees.insert(from.into(), range_tree);
+impl Almanac {
+    /// Overlapping and overflowing mapping lines, ordered by line.
+    pub fn validate(&self) -> Vec<Issue> {
+        validate::validate(&self.maps)
     }
 
-    let mut work = seeds
-        .into_iter()
-        .map(|(start, length)| (start..start + length).map(|seed| ("seed".into(), seed)))
-        .flatten();
+    /// The function converting category `from` straight into `to`.
+    pub fn convert(&self, from: &str, to: &str) -> Result<Piecewise> {
+        compose(&self.maps, from, to)
+    }
 
-    let mut min_location = u64::MAX;
+    /// The function taking a seed straight to its location.
+    pub fn seed_to_location(&self) -> Result<Piecewise> {
+        self.convert("seed", "location")
+    }
 
-    let mut next_work = None;
+    /// Every seed value that ends up at `location`, whether or not it is
+    /// listed in the almanac.
+    pub fn seeds_at(&self, location: u64) -> Result<Vec<u64>> {
+        Ok(self
+            .seed_to_location()?
+            .preimage(location as u128, location as u128 + 1)
+            .into_iter()
+            .flat_map(|(start, end)| start..end)
+            .map(|seed| seed as u64)
+            .collect())
+    }
 
-    loop {
-        if next_work.is_none() {
-            next_work = work.next();
-        }
+    /// The seed ranges whose locations fall in `[start, end)`.
+    pub fn seeds_in(&self, start: u128, end: u128) -> Result<Vec<(u128, u128)>> {
+        Ok(self.seed_to_location()?.preimage(start, end))
+    }
 
-        let Some((name, value)) = next_work.take() else {
-            break;
-        };
-
-        if name == "location" {
-            min_location = std::cmp::min(min_location, value);
-        } else {
-            let target = pairs.get(&name).ok_or(anyhow!("Could not map"))?;
-            if let Some(range) = trees
-                .get(&name)
-                .ok_or(anyhow!("Failed to find map for {name}"))?
-                .find(value, value + 1)
-                .next()
-            {
-                let mapping = &range.val;
-                let new_value = ((value as i64) + mapping.offset()) as u64;
-                next_work = Some((target.clone(), new_value));
-            } else {
-                next_work = Some((target.clone(), value));
+    /// Part two the other way around: scan the location ranges upwards and
+    /// stop once no lower location can be hit by a seed range.
+    pub fn lowest_location_by_scan(&self) -> Result<u64> {
+        let seeds = seed_ranges(&self.seeds)?;
+        let function = self.seed_to_location()?;
+        let mut segments = function.segments().to_vec();
+        segments.sort_by_key(|segment| segment.image().0);
+
+        let mut lowest: Option<u64> = None;
+        for segment in segments {
+            if lowest.is_some_and(|lowest| segment.image().0 >= lowest as u128) {
+                break;
+            }
+            for (start, length) in &seeds {
+                let (start, end) = (*start as u128, *start as u128 + *length as u128);
+                let from = segment.start.max(start);
+                if from < segment.end.min(end) {
+                    let location = function.apply(from as u64);
+                    lowest = Some(lowest.map_or(location, |lowest| lowest.min(location)));
+                }
             }
         }
+
+        lowest.ok_or_else(|| anyhow!("There are no seeds to plant"))
     }
+}
+
+fn lowest_location(seeds: &[u64], maps: &Maps) -> Result<u64> {
+    let seeds: Vec<_> = seeds.iter().map(|seed| (*seed, 1)).collect();
+    lowest_location_ranges(&seeds, maps)
+}
+
+/// Seeds are `(start, length)` pairs. Whole ranges are mapped at once, split
+/// wherever they cross a boundary of the composed function.
+fn lowest_location_ranges(seeds: &[(u64, u64)], maps: &Maps) -> Result<u64> {
+    let function = compose(maps, "seed", "location")?;
+
+    seeds
+        .iter()
+        .flat_map(|(start, length)| {
+            let start = *start as u128;
+            function.apply_range(start, start + *length as u128)
+        })
+        .map(|(start, _)| start as u64)
+        .min()
+        .ok_or_else(|| anyhow!("There are no seeds to plant"))
+}
 
-    Ok(min_location)
+pub fn solve1(input: &str) -> Result<u64> {
+    let almanac = parse(input)?;
+    lowest_location(&almanac.seeds, &almanac.maps)
 }
 
-//fn translate(seed: (String, u64), trees:
+pub fn solve2(input: &str) -> Result<u64> {
+    let almanac = parse(input)?;
+    let seeds = seed_ranges(&almanac.seeds)?;
+    lowest_location_ranges(&seeds, &almanac.maps)
+}
 
 #[test]
 fn test_example_1() {
@@ -234,3 +270,134 @@ fn test_example_2() {
     let r = solve2(s);
     assert_eq!(r.unwrap(), 46);
 }
+
+#[test]
+fn test_part_1() {
+    let s = include_str!("../input.txt");
+    let r = solve1(s);
+    assert_eq!(r.unwrap(), 486613012);
+}
+
+#[test]
+fn test_part_2() {
+    let s = include_str!("../input.txt");
+    let r = solve2(s);
+    assert_eq!(r.unwrap(), 56931769);
+}
+
+#[test]
+fn test_seed_to_location() {
+    let almanac = parse(include_str!("input1.txt")).unwrap();
+    let function = almanac.seed_to_location().unwrap();
+    let locations: Vec<_> = [79, 14, 55, 13].map(|seed| function.apply(seed)).into();
+    assert_eq!(locations, vec![82, 43, 86, 35]);
+}
+
+#[test]
+fn test_inverse() {
+    let almanac = parse(include_str!("input1.txt")).unwrap();
+    assert!(almanac.seeds_at(35).unwrap().contains(&13));
+    assert!(almanac
+        .seeds_in(46, 47)
+        .unwrap()
+        .iter()
+        .any(|(start, end)| (*start..*end).contains(&82)));
+    assert_eq!(almanac.lowest_location_by_scan().unwrap(), 46);
+
+    let almanac = parse(include_str!("../input.txt")).unwrap();
+    assert_eq!(almanac.lowest_location_by_scan().unwrap(), 56931769);
+}
+
+#[test]
+fn test_convert() {
+    let almanac = parse(include_str!("input1.txt")).unwrap();
+    // Seed 79 has soil 81 and humidity 78.
+    assert_eq!(almanac.convert("soil", "humidity").unwrap().apply(81), 78);
+
+    let e = almanac.convert("location", "seed").err().unwrap();
+    assert_eq!(e.to_string(), "No maps lead from location to seed");
+}
+
+#[test]
+fn test_validate() {
+    let s =
+        "seeds: 1 2\n\nseed-to-location map:\n10 0 5\n20 3 5\n12 30 2\n0 18446744073709551610 10\n";
+    let almanac = parse(s).unwrap();
+    let issues: Vec<_> = almanac.validate().iter().map(Issue::to_string).collect();
+    assert_eq!(
+        issues,
+        vec![
+            "line 5: seed-to-location source range overlaps line 4",
+            "line 6: seed-to-location destination range overlaps line 4",
+            "line 7: seed-to-location range runs past 18446744073709551615",
+        ]
+    );
+
+    // The first listed line wins for seed 3, and the overflowing one is cut short.
+    let function = almanac.seed_to_location().unwrap();
+    assert_eq!(function.apply(3), 13);
+    assert_eq!(function.apply(u64::MAX - 1), 4);
+    assert_eq!(function.apply(u64::MAX), 5);
+
+    let strict = Dec5 { mode: Mode::Strict };
+    assert!(strict.parse(s).is_err());
+    assert!(strict.parse(include_str!("../input.txt")).is_ok());
+
+    // Ranges may end right after `u64::MAX`, in either direction.
+    let s = "seeds: 1\n\nseed-to-location map:\n5 18446744073709551615 1\n\
+             18446744073709551615 7 1\n";
+    let almanac = strict.parse(s).unwrap();
+    assert_eq!(almanac.validate(), vec![]);
+    let function = almanac.seed_to_location().unwrap();
+    assert_eq!(function.apply(u64::MAX), 5);
+    assert_eq!(function.apply(7), u64::MAX);
+}
+
+#[test]
+fn test_half_way_shift() {
+    // Shifting by 2^63 there and back again, which does not fit in an i64.
+    let s = "seeds: 3\n\nseed-to-soil map:\n9223372036854775808 0 10\n\n\
+             soil-to-location map:\n0 9223372036854775808 10\n";
+    assert_eq!(solve1(s).unwrap(), 3);
+    // Seed 2^63 + 3 is not in the first map, and goes to 3 in the second.
+    assert_eq!(
+        parse(s).unwrap().seeds_at(3).unwrap(),
+        [3, 9223372036854775811]
+    );
+}
+
+#[test]
+fn test_seed_range_bounds() {
+    let maps = "\n\nseed-to-location map:\n0 10 5\n";
+    let s = format!("seeds: 18446744073709551610 10{maps}");
+    assert_eq!(solve2(&s).unwrap(), 18446744073709551610);
+
+    // The last seed is as good as any other.
+    let s = format!("seeds: 18446744073709551615{maps}");
+    assert_eq!(solve1(&s).unwrap(), u64::MAX);
+    let s = format!("seeds: 18446744073709551615 4{maps}");
+    assert_eq!(solve2(&s).unwrap(), u64::MAX);
+
+    let s = format!("seeds: 7 0{maps}");
+    let e = solve2(&s).unwrap_err();
+    assert_eq!(e.to_string(), "There are no seeds to plant");
+}
+
+#[test]
+fn test_scan_bounds() {
+    let maps = "\n\nseed-to-location map:\n0 10 5\n";
+    let almanac = parse(&format!("seeds: 18446744073709551610 10{maps}")).unwrap();
+    assert_eq!(
+        almanac.lowest_location_by_scan().unwrap(),
+        18446744073709551610
+    );
+    assert_eq!(almanac.seeds_at(u64::MAX).unwrap(), [u64::MAX]);
+    assert_eq!(almanac.seeds_at(u64::MAX - 1).unwrap(), [u64::MAX - 1]);
+
+    let almanac = parse(&format!("seeds: 18446744073709551615 1{maps}")).unwrap();
+    assert_eq!(almanac.lowest_location_by_scan().unwrap(), u64::MAX);
+
+    let almanac = parse(&format!("seeds: 7 0{maps}")).unwrap();
+    let e = almanac.lowest_location_by_scan().unwrap_err();
+    assert_eq!(e.to_string(), "There are no seeds to plant");
+}
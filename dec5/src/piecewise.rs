@@ -0,0 +1,244 @@
+use std::fmt;
+
+/// One past the largest value, `u64::MAX`. Ranges are given as `u128` so that
+/// they can end here.
+pub const END: u128 = 1 << 64;
+
+/// Values in `[start, end)` are shifted by `offset`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Segment {
+    pub start: u128,
+    pub end: u128,
+    pub offset: i128,
+}
+
+impl Segment {
+    fn shift(&self, value: u128) -> u128 {
+        (value as i128 + self.offset) as u128
+    }
+
+    fn unshift(&self, value: u128) -> u128 {
+        (value as i128 - self.offset) as u128
+    }
+
+    /// The range this segment maps onto.
+    pub fn image(&self) -> (u128, u128) {
+        (self.shift(self.start), self.shift(self.end))
+    }
+}
+
+/// A piecewise linear function on the `u64` values, `[0, END)`. The segments
+/// are sorted, contiguous and cover the whole domain, adjacent segments never
+/// share an offset.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Piecewise {
+    segments: Vec<Segment>,
+}
+
+impl Piecewise {
+    pub fn identity() -> Self {
+        Self {
+            segments: vec![Segment {
+                start: 0,
+                end: END,
+                offset: 0,
+            }],
+        }
+    }
+
+    /// Build a function from `(start, end, offset)` ranges. Values outside
+    /// every range map to themselves. Where ranges overlap, the one listed
+    /// first wins. Ranges are cut short where they, or what they map onto,
+    /// leave the domain.
+    pub fn from_ranges(ranges: impl IntoIterator<Item = (u128, u128, i128)>) -> Self {
+        let mut claimed: Vec<Segment> = Vec::new();
+        for (start, end, offset) in ranges {
+            let start = start.max((-offset).max(0) as u128);
+            let end = end.min(END).min((END as i128 - offset).max(0) as u128);
+            if start >= end {
+                continue;
+            }
+            // Only what earlier ranges left free is claimed.
+            let mut free = vec![(start, end)];
+            for segment in &claimed {
+                free = free
+                    .into_iter()
+                    .flat_map(|(start, end)| {
+                        if segment.end <= start || end <= segment.start {
+                            return vec![(start, end)];
+                        }
+                        vec![(start, segment.start), (segment.end, end)]
+                    })
+                    .filter(|(start, end)| start < end)
+                    .collect();
+            }
+            claimed.extend(
+                free.into_iter()
+                    .map(|(start, end)| Segment { start, end, offset }),
+            );
+        }
+        claimed.sort_by_key(|segment| segment.start);
+
+        let mut segments = Vec::new();
+        let mut cursor = 0;
+        for segment in claimed {
+            if cursor < segment.start {
+                segments.push(Segment {
+                    start: cursor,
+                    end: segment.start,
+                    offset: 0,
+                });
+            }
+            cursor = segment.end;
+            segments.push(segment);
+        }
+        if cursor < END {
+            segments.push(Segment {
+                start: cursor,
+                end: END,
+                offset: 0,
+            });
+        }
+
+        Self::merged(segments)
+    }
+
+    /// Join neighbours with equal offsets.
+    fn merged(segments: Vec<Segment>) -> Self {
+        let mut merged: Vec<Segment> = Vec::with_capacity(segments.len());
+        for segment in segments {
+            match merged.last_mut() {
+                Some(last) if last.offset == segment.offset => last.end = segment.end,
+                _ => merged.push(segment),
+            }
+        }
+        Self { segments: merged }
+    }
+
+    pub fn segments(&self) -> &[Segment] {
+        &self.segments
+    }
+
+    /// Index of the segment containing `value`.
+    fn find(&self, value: u128) -> usize {
+        self.segments
+            .partition_point(|segment| segment.end <= value)
+    }
+
+    pub fn apply(&self, value: u64) -> u64 {
+        let value = value as u128;
+        self.segments[self.find(value)].shift(value) as u64
+    }
+
+    /// Map the half open range `[start, end)`, split wherever it crosses a
+    /// segment boundary.
+    pub fn apply_range(&self, start: u128, end: u128) -> Vec<(u128, u128)> {
+        if start >= end {
+            return Vec::new();
+        }
+        self.segments[self.find(start)..]
+            .iter()
+            .take_while(|segment| segment.start < end)
+            .map(|segment| {
+                let from = segment.start.max(start);
+                let to = segment.end.min(end);
+                (segment.shift(from), segment.shift(to))
+            })
+            .collect()
+    }
+
+    /// Every input that lands in `[start, end)`, as sorted and merged ranges.
+    /// The function need not be injective, so this can be several ranges even
+    /// for a single value.
+    pub fn preimage(&self, start: u128, end: u128) -> Vec<(u128, u128)> {
+        let mut ranges: Vec<(u128, u128)> = self
+            .segments
+            .iter()
+            .filter_map(|segment| {
+                let (image_start, image_end) = segment.image();
+                let from = image_start.max(start);
+                let to = image_end.min(end);
+                (from < to).then(|| (segment.unshift(from), segment.unshift(to)))
+            })
+            .collect();
+        ranges.sort();
+
+        let mut merged: Vec<(u128, u128)> = Vec::with_capacity(ranges.len());
+        for (start, end) in ranges {
+            match merged.last_mut() {
+                Some(last) if last.1 >= start => last.1 = last.1.max(end),
+                _ => merged.push((start, end)),
+            }
+        }
+        merged
+    }
+
+    /// The function that applies `self` and then `next`.
+    pub fn then(&self, next: &Piecewise) -> Piecewise {
+        let mut segments = Vec::new();
+        for segment in &self.segments {
+            let (start, end) = segment.image();
+            for inner in next.segments[next.find(start)..]
+                .iter()
+                .take_while(|inner| inner.start < end)
+            {
+                segments.push(Segment {
+                    start: segment.unshift(inner.start.max(start)),
+                    end: segment.unshift(inner.end.min(end)),
+                    offset: segment.offset + inner.offset,
+                });
+            }
+        }
+        Self::merged(segments)
+    }
+}
+
+impl fmt::Display for Piecewise {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for segment in &self.segments {
+            writeln!(
+                f,
+                "[{}, {}) {:+}",
+                segment.start, segment.end, segment.offset
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn test_from_ranges() {
+    let f = Piecewise::from_ranges([(98, 100, -48), (50, 98, 2)]);
+    assert_eq!(f.apply(10), 10);
+    assert_eq!(f.apply(79), 81);
+    assert_eq!(f.apply(99), 51);
+    assert_eq!(f.to_string().lines().count(), 4);
+
+    let f = Piecewise::from_ranges([(10, 20, 1), (5, 15, 2)]);
+    assert_eq!([5, 9, 10, 19, 20].map(|v| f.apply(v)), [7, 11, 11, 20, 20]);
+
+    // Only the part landing in the domain is kept.
+    let f = Piecewise::from_ranges([(0, 10, END as i128 - 5)]);
+    assert_eq!(f.apply(4), u64::MAX);
+    assert_eq!(f.apply(5), 5);
+}
+
+#[test]
+fn test_then() {
+    let f = Piecewise::from_ranges([(0, 10, 5)]);
+    let g = Piecewise::from_ranges([(8, 12, 100)]);
+    let h = f.then(&g);
+    for value in 0..20 {
+        assert_eq!(h.apply(value), g.apply(f.apply(value)));
+    }
+    assert_eq!(h.apply_range(0, 10), vec![(5, 8), (108, 112), (12, 15)]);
+}
+
+#[test]
+fn test_preimage() {
+    // 50..52 maps onto 98..100, which is also the identity image of 98..100.
+    let f = Piecewise::from_ranges([(50, 52, 48)]);
+    assert_eq!(f.preimage(99, 100), vec![(51, 52), (99, 100)]);
+    assert_eq!(f.preimage(49, 53), vec![(49, 50), (52, 53)]);
+    assert_eq!(f.preimage(50, 52), vec![]);
+}
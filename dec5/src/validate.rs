@@ -0,0 +1,96 @@
+use std::fmt;
+
+use crate::piecewise::END;
+use crate::Maps;
+
+/// A suspicious mapping line in an almanac.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Issue {
+    /// The source range overlaps the one of an earlier line of the same map.
+    Overlap {
+        map: String,
+        line: usize,
+        earlier: usize,
+    },
+    /// The destination range overlaps the one of an earlier line of the same
+    /// map, so some values have several sources.
+    Collision {
+        map: String,
+        line: usize,
+        earlier: usize,
+    },
+    /// The source or destination range runs past `u64::MAX`.
+    Overflow { map: String, line: usize },
+}
+
+impl Issue {
+    pub fn line(&self) -> usize {
+        match self {
+            Issue::Overlap { line, .. }
+            | Issue::Collision { line, .. }
+            | Issue::Overflow { line, .. } => *line,
+        }
+    }
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Issue::Overlap { map, line, earlier } => {
+                write!(f, "line {line}: {map} source range overlaps line {earlier}")
+            }
+            Issue::Collision { map, line, earlier } => write!(
+                f,
+                "line {line}: {map} destination range overlaps line {earlier}"
+            ),
+            Issue::Overflow { map, line } => {
+                write!(f, "line {line}: {map} range runs past {}", u64::MAX)
+            }
+        }
+    }
+}
+
+fn overlaps(a: (u128, u128), b: (u128, u128)) -> bool {
+    a.0 < b.1 && b.0 < a.1
+}
+
+/// Check every map for overlapping source ranges, overlapping destination
+/// ranges and ranges that do not fit in a `u64`.
+pub(crate) fn validate(maps: &Maps) -> Vec<Issue> {
+    let mut issues = Vec::new();
+
+    for ((from, to), mappings) in maps {
+        let map = format!("{from}-to-{to}");
+        for (i, mapping) in mappings.iter().enumerate() {
+            let source = mapping.source();
+            let destination = mapping.destination();
+
+            if source.1 > END || destination.1 > END {
+                issues.push(Issue::Overflow {
+                    map: map.clone(),
+                    line: mapping.line,
+                });
+            }
+
+            for earlier in &mappings[..i] {
+                if overlaps(earlier.source(), source) {
+                    issues.push(Issue::Overlap {
+                        map: map.clone(),
+                        line: mapping.line,
+                        earlier: earlier.line,
+                    });
+                }
+                if overlaps(earlier.destination(), destination) {
+                    issues.push(Issue::Collision {
+                        map: map.clone(),
+                        line: mapping.line,
+                        earlier: earlier.line,
+                    });
+                }
+            }
+        }
+    }
+
+    issues.sort_by_key(Issue::line);
+    issues
+}
@@ -1,113 +1,141 @@
-use anyhow::anyhow;
-use anyhow::Result;
+use anyhow::{anyhow, Result};
+use common::{parse_all, Answer, ParseError, Puzzle};
 use nom::character::complete::digit1;
-use nom::character::complete::{multispace0, multispace1, u64 as nom_u64};
-use nom::combinator::map;
-use nom::error::Error;
-use nom::sequence::{preceded, separated_pair, terminated};
+use nom::character::complete::multispace1;
+use nom::error::{context, VerboseError};
+use nom::sequence::{preceded, separated_pair};
 use nom::{bytes::complete::tag, character::complete::space1, multi::separated_list1};
 
-fn parse(input: &str) -> Result<Vec<(u64, u64)>> {
-    let time = preceded(
-        tag("Time:"),
-        preceded(space1, separated_list1(space1::<_, Error<_>>, nom_u64)),
-    );
-    let distance = preceded(
-        tag("Distance:"),
-        preceded(space1, separated_list1(space1, nom_u64)),
-    );
+/// The columns are kept as digits, part two reads them as a single number
+/// with the spaces removed.
+#[derive(Debug)]
+pub struct Races {
+    times: Vec<String>,
+    distances: Vec<String>,
+}
+
+pub struct Dec6;
+
+impl Puzzle for Dec6 {
+    const DAY: u8 = 6;
+
+    type Model = Races;
+
+    fn parse(&self, input: &str) -> Result<Races> {
+        Ok(parse(input)?)
+    }
+
+    fn part1(&self, races: &Races) -> Result<Answer> {
+        Ok(margin(races)?.into())
+    }
 
-    let mut parser = terminated(separated_pair(time, multispace1, distance), multispace0);
-
-    let (times, distances) = match parser(input) {
-        Ok((remaining, input)) if remaining == "" => Ok(input),
-        Ok((input, _)) => {
-            println!("Remaining input:");
-            println!("{input:?}");
-            Err(anyhow!("Failed to parse"))
-        }
-        Err(e) => {
-            println!("Failed to parse");
-            println!("{e:?}");
-            Err(anyhow!("Failed to parse"))
-        }
-    }?;
-
-    Ok(times.into_iter().zip(distances.into_iter()).collect())
+    fn part2(&self, races: &Races) -> Result<Answer> {
+        Ok(kerned(races)?.into())
+    }
 }
 
-fn parse2(input: &str) -> Result<(u64, u64)> {
-    let time = map(
+fn parse(input: &str) -> Result<Races, ParseError> {
+    let time = context(
+        "times",
         preceded(
             tag("Time:"),
-            preceded(space1, separated_list1(space1::<_, Error<_>>, digit1)),
+            preceded(space1, separated_list1(space1::<_, VerboseError<_>>, digit1)),
         ),
-        |digits| digits.join("").parse::<u64>().unwrap(),
     );
-
-    let distance = map(
+    let distance = context(
+        "distances",
         preceded(
             tag("Distance:"),
             preceded(space1, separated_list1(space1, digit1)),
         ),
-        |digits| digits.join("").parse::<u64>().unwrap(),
     );
 
-    let mut parser = terminated(separated_pair(time, multispace1, distance), multispace0);
-
-    match parser(input) {
-        Ok((remaining, input)) if remaining == "" => Ok(input),
-        Ok((input, _)) => {
-            println!("Remaining input:");
-            println!("{input:?}");
-            Err(anyhow!("Failed to parse"))
-        }
-        Err(e) => {
-            println!("Failed to parse");
-            println!("{e:?}");
-            Err(anyhow!("Failed to parse"))
-        }
+    let (times, distances) = parse_all(separated_pair(time, multispace1, distance), input)?;
+
+    if times.len() != distances.len() {
+        let last = distances[distances.len() - 1];
+        let extra = distances
+            .get(times.len())
+            .copied()
+            .unwrap_or(&last[last.len()..]);
+        return Err(ParseError::at(
+            input,
+            extra,
+            format!("{} distances", times.len()),
+        ));
     }
 
+    Ok(Races {
+        times: times.into_iter().map(String::from).collect(),
+        distances: distances.into_iter().map(String::from).collect(),
+    })
 }
 
+/// Largest `x` with `x * x <= n`. The float estimate is only off by a little,
+/// so a few steps either way make it exact.
+fn isqrt(n: u128) -> u128 {
+    let mut x = (n as f64).sqrt() as u128;
+    while x.checked_mul(x).is_none_or(|square| square > n) {
+        x -= 1;
+    }
+    while (x + 1).checked_mul(x + 1).is_some_and(|square| square <= n) {
+        x += 1;
+    }
+    x
+}
+
+/// Holding for `h` out of `length` goes `h * (length - h)`, which beats the
+/// record for every `h` strictly between the roots of
+/// `h * h - length * h + record`. The count is symmetric around
+/// `length / 2`, so only the lowest winning hold is needed.
 fn do_one(length: u64, record: u64) -> Result<u64> {
-    // s * t-s  > r
-    // st * ss > r
-    let rt = length as f64;
-    let record = record as f64;
-
-    let lower = 0.5f64 * (rt - f64::sqrt(rt * rt - 4f64 * record));
-    let upper = 0.5f64 * (rt + f64::sqrt(rt * rt - 4f64 * record));
-
-    let lower: u64 = if lower.ceil() - lower == 0f64 {
-        (lower + 1f64) as u64
-    } else {
-        lower.ceil() as u64
-    };
-
-    let upper: u64 = if upper - upper.floor() == 0f64 {
-        (upper - 1f64) as u64
-    } else {
-        upper.floor() as u64
-    };
-
-    Ok(upper - lower + 1)
+    let (t, r) = (length as u128, record as u128);
+    let wins = |hold: u128| hold * (t - hold) > r;
+
+    if !wins(t / 2) {
+        return Err(anyhow!(
+            "No hold time beats the record of {record} in a race of {length}"
+        ));
+    }
+
+    // t * t >= 4 * r, since the best hold beats the record.
+    let mut lower = (t - isqrt(t * t - 4 * r)) / 2;
+    while lower > 0 && wins(lower - 1) {
+        lower -= 1;
+    }
+    while !wins(lower) {
+        lower += 1;
+    }
+
+    Ok((t - 2 * lower + 1) as u64)
 }
 
-pub fn solve1(input: &str) -> Result<u64> {
-    let a: Vec<u64> = parse(input)?
-        .into_iter()
-        .map(|(a, b)| do_one(a, b))
+fn margin(races: &Races) -> Result<u64> {
+    let a: Vec<u64> = races
+        .times
+        .iter()
+        .zip(races.distances.iter())
+        .map(|(a, b)| do_one(a.parse()?, b.parse()?))
         .collect::<Result<Vec<u64>>>()?;
     Ok(a.into_iter().reduce(|acc, v| acc * v).unwrap_or(0))
 }
 
-pub fn solve2(input: &str) -> Result<u64> {
-    let (time, record) = parse2(input)?;
+fn kerned(races: &Races) -> Result<u64> {
+    let time = races.times.join("").parse()?;
+    let record = races.distances.join("").parse()?;
     do_one(time, record)
 }
 
+pub fn solve1(input: &str) -> Result<u64> {
+    let races = parse(input)?;
+    margin(&races)
+}
+
+pub fn solve2(input: &str) -> Result<u64> {
+    let races = parse(input)?;
+    kerned(&races)
+}
+
 #[test]
 fn test_example_1() {
     let s = include_str!("input1.txt");
@@ -121,3 +149,30 @@ fn test_example_2() {
     let r = solve2(s);
     assert_eq!(r.unwrap(), 71503);
 }
+
+#[test]
+fn test_missing_distance() {
+    let s = "Time: 7 15 30\nDistance: 9 40\n";
+    let e = parse(s).unwrap_err();
+    assert_eq!((e.line, e.column), (2, 15));
+    assert_eq!(e.expected, "3 distances");
+}
+
+#[test]
+fn test_do_one() {
+    assert_eq!(do_one(7, 9).unwrap(), 4);
+    assert_eq!(do_one(30, 200).unwrap(), 9);
+
+    // Far past where a f64 holds `length * length` exactly. The record is
+    // matched exactly by holding for `hold`, so the wins are strictly inside.
+    let (length, hold) = (100_000_000_007u64, 12_345_678u64);
+    let record = hold * (length - hold);
+    assert_eq!(do_one(length, record).unwrap(), length - 2 * hold - 1);
+    assert_eq!(do_one(length, record - 1).unwrap(), length - 2 * hold + 1);
+
+    let e = do_one(4, 4).unwrap_err();
+    assert_eq!(
+        e.to_string(),
+        "No hold time beats the record of 4 in a race of 4"
+    );
+}
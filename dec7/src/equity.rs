@@ -0,0 +1,357 @@
+use anyhow::{anyhow, Result};
+
+use crate::{Hand, Ruleset};
+
+/// What the missing cards of a hand are drawn from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Deck {
+    /// Every card of the ruleset is equally likely on every draw.
+    Endless,
+    /// This many of each card, less the ones already in sight.
+    Copies(usize),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Method {
+    /// Weigh every possible completion.
+    Exact,
+    /// Draw this many random completions.
+    Sample { hands: usize, seed: u64 },
+}
+
+/// How a partial hand is likely to turn out.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Equity {
+    /// Probability of each category of the ruleset, in its order.
+    pub categories: Vec<f64>,
+    /// Probability of beating and of tying the opponent, if there is one.
+    pub win: Option<f64>,
+    pub tie: Option<f64>,
+}
+
+/// The cards left to draw from, by strength.
+struct Draw {
+    labels: Vec<char>,
+    /// `None` for an endless deck.
+    left: Option<Vec<usize>>,
+}
+
+impl Draw {
+    fn new(ruleset: &Ruleset, deck: Deck, seen: impl Iterator<Item = char>) -> Result<Self> {
+        let labels: Vec<char> = ruleset.cards.chars().collect();
+        let left = match deck {
+            Deck::Endless => None,
+            Deck::Copies(copies) => {
+                let mut left = vec![copies; labels.len()];
+                for label in seen {
+                    let i = labels
+                        .iter()
+                        .position(|card| *card == label)
+                        .ok_or_else(|| anyhow!("Unknown card {label}"))?;
+                    left[i] = left[i]
+                        .checked_sub(1)
+                        .ok_or_else(|| anyhow!("The deck only has {copies} of card {label}"))?;
+                }
+                Some(left)
+            }
+        };
+        Ok(Self { labels, left })
+    }
+
+    /// Each card that can be drawn next, with its probability.
+    fn odds(&self) -> Vec<(usize, f64)> {
+        match &self.left {
+            None => {
+                let p = 1.0 / self.labels.len() as f64;
+                (0..self.labels.len()).map(|i| (i, p)).collect()
+            }
+            Some(left) => {
+                let total: usize = left.iter().sum();
+                (0..left.len())
+                    .filter(|i| left[*i] > 0)
+                    .map(|i| (i, left[i] as f64 / total as f64))
+                    .collect()
+            }
+        }
+    }
+
+    /// How many cards are left, `None` if they never run out.
+    fn left(&self) -> Option<usize> {
+        match &self.left {
+            None if self.labels.is_empty() => Some(0),
+            None => None,
+            Some(left) => Some(left.iter().sum()),
+        }
+    }
+
+    fn take(&mut self, i: usize) {
+        if let Some(left) = &mut self.left {
+            left[i] -= 1;
+        }
+    }
+
+    fn put_back(&mut self, i: usize) {
+        if let Some(left) = &mut self.left {
+            left[i] += 1;
+        }
+    }
+}
+
+/// Tallies completed hands.
+struct Tally<'a> {
+    ruleset: &'a Ruleset,
+    opponent: Option<&'a Hand>,
+    categories: Vec<f64>,
+    win: f64,
+    tie: f64,
+}
+
+impl Tally<'_> {
+    fn add(&mut self, labels: &[char], weight: f64) -> Result<()> {
+        let hand = Hand::new(labels, self.ruleset)?;
+        self.categories[hand.category()] += weight;
+        if let Some(opponent) = self.opponent {
+            if hand > *opponent {
+                self.win += weight;
+            } else if hand == *opponent {
+                self.tie += weight;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The odds of each category for a hand whose `None` cards are still to be
+/// drawn, and of it beating `opponent`. Cards in the partial hand and the
+/// opponent's hand are not in the deck.
+pub fn equity(
+    partial: &[Option<char>],
+    opponent: Option<&Hand>,
+    ruleset: &Ruleset,
+    deck: Deck,
+    method: Method,
+) -> Result<Equity> {
+    let seen = partial.iter().flatten().copied().chain(
+        opponent
+            .into_iter()
+            .flat_map(|hand| hand.cards().iter().map(|card| card.label())),
+    );
+    let mut draw = Draw::new(ruleset, deck, seen)?;
+    let mut tally = Tally {
+        ruleset,
+        opponent,
+        categories: vec![0.0; ruleset.categories.len()],
+        win: 0.0,
+        tie: 0.0,
+    };
+
+    let mut labels: Vec<char> = partial.iter().map(|card| card.unwrap_or(' ')).collect();
+    let missing: Vec<usize> = (0..partial.len())
+        .filter(|i| partial[*i].is_none())
+        .collect();
+    if let Some(left) = draw.left().filter(|left| *left < missing.len()) {
+        return Err(anyhow!(
+            "The deck has {left} cards left, the hand is missing {}",
+            missing.len()
+        ));
+    }
+    match method {
+        Method::Exact => exact(&mut labels, &missing, &mut draw, 1.0, &mut tally)?,
+        Method::Sample { hands: 0, .. } => return Err(anyhow!("No hands to sample")),
+        Method::Sample { hands, seed } => {
+            let mut rng = SplitMix64(seed);
+            let weight = 1.0 / hands as f64;
+            for _ in 0..hands {
+                let mut drawn = Vec::with_capacity(missing.len());
+                for slot in &missing {
+                    let i = pick(&draw.odds(), rng.next_f64());
+                    draw.take(i);
+                    drawn.push(i);
+                    labels[*slot] = draw.labels[i];
+                }
+                tally.add(&labels, weight)?;
+                for i in drawn {
+                    draw.put_back(i);
+                }
+            }
+        }
+    }
+
+    Ok(Equity {
+        categories: tally.categories,
+        win: opponent.map(|_| tally.win),
+        tie: opponent.map(|_| tally.tie),
+    })
+}
+
+fn exact(
+    labels: &mut [char],
+    missing: &[usize],
+    draw: &mut Draw,
+    weight: f64,
+    tally: &mut Tally,
+) -> Result<()> {
+    let Some((slot, rest)) = missing.split_first() else {
+        return tally.add(labels, weight);
+    };
+    for (i, p) in draw.odds() {
+        draw.take(i);
+        labels[*slot] = draw.labels[i];
+        exact(labels, rest, draw, weight * p, tally)?;
+        draw.put_back(i);
+    }
+    Ok(())
+}
+
+/// The card whose share of the odds `x` in `[0, 1)` falls in.
+fn pick(odds: &[(usize, f64)], mut x: f64) -> usize {
+    for (i, p) in odds {
+        if x < *p {
+            return *i;
+        }
+        x -= p;
+    }
+    // Rounding can leave `x` just past the last share.
+    odds[odds.len() - 1].0
+}
+
+/// A small, seedable generator, good enough for sampling hands.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in `[0, 1)`.
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+#[cfg(test)]
+fn partial(cards: &str) -> Vec<Option<char>> {
+    cards.chars().map(|c| (c != '_').then_some(c)).collect()
+}
+
+#[cfg(test)]
+fn close(a: f64, b: f64, tolerance: f64) -> bool {
+    (a - b).abs() < tolerance
+}
+
+#[test]
+fn test_exact() {
+    use crate::HandType;
+
+    let rules = Ruleset::standard();
+    let opponent = Hand::new(&['K', 'K', 'K', 'K', 'Q'], &rules).unwrap();
+    let odds = equity(
+        &partial("KKKK_"),
+        Some(&opponent),
+        &rules,
+        Deck::Endless,
+        Method::Exact,
+    )
+    .unwrap();
+    let five = rules.position(HandType::FiveOfAKind).unwrap();
+    let four = rules.position(HandType::FourOfAKind).unwrap();
+    assert!(close(odds.categories[five], 1.0 / 14.0, 1e-9));
+    assert!(close(odds.categories[four], 13.0 / 14.0, 1e-9));
+    // Only a king or an ace as the last card beats the queen.
+    assert!(close(odds.win.unwrap(), 2.0 / 14.0, 1e-9));
+    assert!(close(odds.tie.unwrap(), 1.0 / 14.0, 1e-9));
+
+    // With five of each card, the opponent holds the last king.
+    let odds = equity(
+        &partial("KKK_K"),
+        Some(&Hand::new(&['Q', 'Q', 'Q', 'Q', 'K'], &rules).unwrap()),
+        &rules,
+        Deck::Copies(5),
+        Method::Exact,
+    )
+    .unwrap();
+    assert_eq!(odds.categories[five], 0.0);
+    assert!(close(odds.categories[four], 1.0, 1e-9));
+
+    let e = equity(
+        &partial("KKK__"),
+        Some(&opponent),
+        &rules,
+        Deck::Copies(4),
+        Method::Exact,
+    )
+    .unwrap_err();
+    assert_eq!(e.to_string(), "The deck only has 4 of card K");
+}
+
+#[test]
+fn test_sample() {
+    let rules = Ruleset::jokers_wild();
+    let opponent = Hand::new(&['Q', 'Q', 'Q', 'J', 'A'], &rules).unwrap();
+    let exact = equity(
+        &partial("J2__"),
+        None,
+        &Ruleset {
+            hand_size: 4,
+            ..rules.clone()
+        },
+        Deck::Copies(4),
+        Method::Exact,
+    )
+    .unwrap();
+    assert!(close(exact.categories.iter().sum(), 1.0, 1e-9));
+    assert_eq!(exact.win, None);
+
+    let hand = partial("JK___");
+    let exact = equity(
+        &hand,
+        Some(&opponent),
+        &rules,
+        Deck::Copies(4),
+        Method::Exact,
+    )
+    .unwrap();
+    let method = Method::Sample {
+        hands: 20000,
+        seed: 7,
+    };
+    let sampled = equity(&hand, Some(&opponent), &rules, Deck::Copies(4), method).unwrap();
+    for (a, b) in exact.categories.iter().zip(&sampled.categories) {
+        assert!(close(*a, *b, 0.02), "{exact:?} {sampled:?}");
+    }
+    assert!(close(exact.win.unwrap(), sampled.win.unwrap(), 0.02));
+}
+
+#[test]
+fn test_empty_deck() {
+    let rules = Ruleset::standard();
+    let sample = Method::Sample { hands: 10, seed: 1 };
+    for method in [Method::Exact, sample] {
+        let e = equity(&[None; 5], None, &rules, Deck::Copies(0), method).unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "The deck has 0 cards left, the hand is missing 5"
+        );
+    }
+
+    let tiny = Ruleset {
+        cards: "AK".to_string(),
+        ..rules
+    };
+    let e = equity(
+        &partial("AK___"),
+        None,
+        &tiny,
+        Deck::Copies(2),
+        Method::Exact,
+    )
+    .unwrap_err();
+    assert_eq!(
+        e.to_string(),
+        "The deck has 2 cards left, the hand is missing 3"
+    );
+}
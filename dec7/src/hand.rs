@@ -0,0 +1,393 @@
+use std::fmt;
+
+use anyhow::{anyhow, Result};
+
+/// The hand categories of the standard game, weakest first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum HandType {
+    HighCard,
+    OnePair,
+    TwoPair,
+    ThreeOfAKind,
+    FullHouse,
+    FourOfAKind,
+    FiveOfAKind,
+}
+
+impl HandType {
+    pub const ALL: [HandType; 7] = [
+        HandType::HighCard,
+        HandType::OnePair,
+        HandType::TwoPair,
+        HandType::ThreeOfAKind,
+        HandType::FullHouse,
+        HandType::FourOfAKind,
+        HandType::FiveOfAKind,
+    ];
+
+    fn groups(&self) -> Vec<usize> {
+        match self {
+            HandType::HighCard => vec![1],
+            HandType::OnePair => vec![2],
+            HandType::TwoPair => vec![2, 2],
+            HandType::ThreeOfAKind => vec![3],
+            HandType::FullHouse => vec![3, 2],
+            HandType::FourOfAKind => vec![4],
+            HandType::FiveOfAKind => vec![5],
+        }
+    }
+}
+
+impl fmt::Display for HandType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            HandType::HighCard => "high card",
+            HandType::OnePair => "one pair",
+            HandType::TwoPair => "two pair",
+            HandType::ThreeOfAKind => "three of a kind",
+            HandType::FullHouse => "full house",
+            HandType::FourOfAKind => "four of a kind",
+            HandType::FiveOfAKind => "five of a kind",
+        };
+        f.write_str(name)
+    }
+}
+
+/// A kind of hand, made of groups of equal cards. A hand has it if, with
+/// both sorted largest first, its largest group has at least as many cards as
+/// the largest of `groups`, the next largest at least as many as the next,
+/// and so on. `groups` may be listed in any order.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Category {
+    pub name: String,
+    pub groups: Vec<usize>,
+    /// The standard hand type this is, if any.
+    pub kind: Option<HandType>,
+}
+
+impl From<HandType> for Category {
+    fn from(kind: HandType) -> Self {
+        Self {
+            name: kind.to_string(),
+            groups: kind.groups(),
+            kind: Some(kind),
+        }
+    }
+}
+
+impl fmt::Display for Category {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.name)
+    }
+}
+
+/// What a game of Camel Cards is played with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ruleset {
+    pub hand_size: usize,
+    /// Every card, weakest first.
+    pub cards: String,
+    /// The cards that count as whatever other card makes the best hand.
+    pub wild: String,
+    /// Weakest first. A hand takes the strongest category it has.
+    pub categories: Vec<Category>,
+}
+
+impl Default for Ruleset {
+    fn default() -> Self {
+        Self::standard()
+    }
+}
+
+impl Ruleset {
+    pub fn standard() -> Self {
+        Self {
+            hand_size: 5,
+            cards: "123456789TJQKA".to_string(),
+            wild: String::new(),
+            categories: HandType::ALL.into_iter().map(Category::from).collect(),
+        }
+    }
+
+    /// Jokers are the weakest card, but wild.
+    pub fn jokers_wild() -> Self {
+        Self {
+            cards: "J123456789TQKA".to_string(),
+            wild: "J".to_string(),
+            ..Self::standard()
+        }
+    }
+
+    fn is_wild(&self, label: char) -> bool {
+        self.wild.contains(label)
+    }
+
+    /// The category a hand was ranked in.
+    pub fn category(&self, hand: &Hand) -> &Category {
+        &self.categories[hand.category]
+    }
+
+    /// Where a standard hand type is among the categories, if it is there.
+    pub fn position(&self, kind: HandType) -> Option<usize> {
+        self.categories
+            .iter()
+            .position(|category| category.kind == Some(kind))
+    }
+}
+
+/// A card, ordered by its strength under the ruleset it was made with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Card {
+    strength: u8,
+    label: char,
+}
+
+impl Card {
+    pub fn new(label: char, ruleset: &Ruleset) -> Result<Self> {
+        let strength = ruleset
+            .cards
+            .chars()
+            .position(|card| card == label)
+            .ok_or_else(|| anyhow!("Unknown card {label}"))?;
+        Ok(Self {
+            strength: strength as u8,
+            label,
+        })
+    }
+
+    pub fn label(&self) -> char {
+        self.label
+    }
+}
+
+/// A hand ordered by its category, then card by card.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Hand {
+    category: usize,
+    /// Follows from `category`, so it never decides the order.
+    kind: Option<HandType>,
+    cards: Vec<Card>,
+}
+
+impl Hand {
+    pub fn new(labels: &[char], ruleset: &Ruleset) -> Result<Self> {
+        if labels.len() != ruleset.hand_size {
+            return Err(anyhow!(
+                "Hand {} has {} cards instead of {}",
+                labels.iter().collect::<String>(),
+                labels.len(),
+                ruleset.hand_size
+            ));
+        }
+        let cards = labels
+            .iter()
+            .map(|label| Card::new(*label, ruleset))
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut counts = vec![0; ruleset.cards.chars().count()];
+        let mut wilds = 0;
+        for card in &cards {
+            if ruleset.is_wild(card.label) {
+                wilds += 1;
+            } else {
+                counts[card.strength as usize] += 1;
+            }
+        }
+        let plain = ruleset
+            .cards
+            .chars()
+            .filter(|c| !ruleset.is_wild(*c))
+            .count();
+        let mut counts: Vec<_> = counts.into_iter().filter(|count| *count > 0).collect();
+        counts.sort_unstable_by(|a, b| b.cmp(a));
+
+        // Matching the largest groups of the category with the largest groups
+        // in the hand needs the fewest wild cards to make up the difference.
+        let has = |category: &Category| {
+            let mut sorted;
+            let mut groups = &category.groups;
+            if groups.windows(2).any(|pair| pair[0] < pair[1]) {
+                sorted = groups.clone();
+                sorted.sort_unstable_by(|a, b| b.cmp(a));
+                groups = &sorted;
+            }
+            let missing: usize = groups
+                .iter()
+                .enumerate()
+                .map(|(i, group)| group.saturating_sub(counts.get(i).copied().unwrap_or(0)))
+                .sum();
+            groups.len() <= plain && missing <= wilds
+        };
+        let category = ruleset
+            .categories
+            .iter()
+            .rposition(has)
+            .ok_or_else(|| anyhow!("Hand {} is in no category", Self::label(&cards)))?;
+
+        Ok(Self {
+            category,
+            kind: ruleset.categories[category].kind,
+            cards,
+        })
+    }
+
+    /// Index of the hand's category in the ruleset it was made with.
+    pub fn category(&self) -> usize {
+        self.category
+    }
+
+    /// The standard hand type of the hand's category, if it is one.
+    pub fn hand_type(&self) -> Option<HandType> {
+        self.kind
+    }
+
+    pub fn cards(&self) -> &[Card] {
+        &self.cards
+    }
+
+    fn label(cards: &[Card]) -> String {
+        cards.iter().map(Card::label).collect()
+    }
+}
+
+impl fmt::Display for Hand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&Self::label(&self.cards))
+    }
+}
+
+#[cfg(test)]
+fn hand(labels: &str, ruleset: &Ruleset) -> Result<Hand> {
+    Hand::new(&labels.chars().collect::<Vec<_>>(), ruleset)
+}
+
+#[test]
+fn test_hand() {
+    let standard = Ruleset::standard();
+    let jokers = Ruleset::jokers_wild();
+
+    let plain = hand("KTJJT", &standard).unwrap();
+    let wild = hand("KTJJT", &jokers).unwrap();
+    assert_eq!(standard.category(&plain).to_string(), "two pair");
+    assert_eq!(jokers.category(&wild).to_string(), "four of a kind");
+    assert_eq!(wild.hand_type(), Some(HandType::FourOfAKind));
+    assert_eq!(wild.to_string(), "KTJJT");
+
+    // Same category, so the first differing card decides.
+    assert!(hand("KK677", &standard).unwrap() > plain);
+    assert!(hand("JKKK2", &jokers).unwrap() < hand("QQQQ2", &jokers).unwrap());
+
+    assert!(hand("KX123", &standard).is_err());
+    assert!(hand("KK", &standard).is_err());
+}
+
+#[test]
+fn test_variant() {
+    // Seven cards, two kinds of wild card and a category that beats four of
+    // a kind.
+    let mut categories: Vec<Category> = HandType::ALL.into_iter().map(Category::from).collect();
+    categories.push(Category {
+        name: "two triples".to_string(),
+        groups: vec![3, 3],
+        kind: None,
+    });
+    let rules = Ruleset {
+        hand_size: 7,
+        cards: "*?23456789".to_string(),
+        wild: "*?".to_string(),
+        categories,
+    };
+
+    let category = |labels| rules.category(&hand(labels, &rules).unwrap()).to_string();
+    assert_eq!(category("2345678"), "high card");
+    assert_eq!(category("2223334"), "two triples");
+    // The wild cards do better as a second triple than as a fifth 2.
+    assert_eq!(category("2223*?4"), "two triples");
+    assert_eq!(category("22*?567"), "four of a kind");
+    assert_eq!(hand("2223334", &rules).unwrap().hand_type(), None);
+}
+
+#[test]
+fn test_unsorted_groups() {
+    let mut rules = Ruleset::standard();
+    rules.categories.push(Category {
+        name: "pair and triple".to_string(),
+        groups: vec![2, 3],
+        kind: None,
+    });
+    let category = |labels| rules.category(&hand(labels, &rules).unwrap()).to_string();
+    assert_eq!(category("22233"), "pair and triple");
+    assert_eq!(category("22234"), "three of a kind");
+}
+
+/// Try every card for every wild card.
+#[cfg(test)]
+fn brute_force(labels: &[char], ruleset: &Ruleset) -> usize {
+    let plain = Ruleset {
+        wild: String::new(),
+        ..ruleset.clone()
+    };
+    let order: Vec<char> = ruleset
+        .cards
+        .chars()
+        .filter(|c| !ruleset.is_wild(*c))
+        .collect();
+    let wilds: Vec<usize> = (0..labels.len())
+        .filter(|i| ruleset.is_wild(labels[*i]))
+        .collect();
+
+    let mut best = 0;
+    let mut choice = vec![0; wilds.len()];
+    loop {
+        let mut labels = labels.to_vec();
+        for (i, card) in wilds.iter().zip(&choice) {
+            labels[*i] = order[*card];
+        }
+        best = best.max(Hand::new(&labels, &plain).unwrap().category());
+
+        let Some(i) = choice.iter().position(|card| *card < order.len() - 1) else {
+            break;
+        };
+        choice[..i].fill(0);
+        choice[i] += 1;
+    }
+    best
+}
+
+/// The order of the cards does not matter to the category, so every sorted
+/// hand covers all hands.
+#[cfg(test)]
+fn check_wild_cards(ruleset: &Ruleset) {
+    let order: Vec<char> = ruleset.cards.chars().collect();
+    let mut hand = vec![0; ruleset.hand_size];
+    loop {
+        let labels: Vec<char> = hand.iter().map(|card| order[*card]).collect();
+        assert_eq!(
+            Hand::new(&labels, ruleset).unwrap().category(),
+            brute_force(&labels, ruleset),
+            "{labels:?}"
+        );
+
+        let Some(i) = hand.iter().rposition(|card| *card < order.len() - 1) else {
+            break;
+        };
+        let next = hand[i] + 1;
+        hand[i..].fill(next);
+    }
+}
+
+#[test]
+fn test_jokers() {
+    check_wild_cards(&Ruleset::jokers_wild());
+
+    // Full houses beat four of a kind, so wild cards cannot all go to the
+    // largest group.
+    let mut categories: Vec<Category> = HandType::ALL.into_iter().map(Category::from).collect();
+    categories.swap(4, 5);
+    check_wild_cards(&Ruleset {
+        hand_size: 6,
+        cards: "*?2345".to_string(),
+        wild: "*?".to_string(),
+        categories,
+    });
+}
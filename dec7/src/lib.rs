@@ -1,214 +1,120 @@
-use std::cmp::max;
-use std::cmp::Ordering::{Equal, Greater, Less};
-
-use anyhow::{anyhow, Result};
+use anyhow::Result;
+use common::{parse_all, Answer, ParseError, Puzzle};
 use nom::character::complete::u64 as nom_u64;
-use nom::combinator::map;
 use nom::{
-    character::complete::{multispace0, newline, one_of, space1},
-    combinator::verify,
-    error::ErrorKind,
-    multi::{many1, separated_list1},
-    sequence::{separated_pair, terminated},
+    bytes::complete::take_till1,
+    character::complete::{newline, space1},
+    error::{context, VerboseError},
+    multi::separated_list1,
+    sequence::separated_pair,
 };
 
-fn parse(input: &str) -> Result<Vec<(Vec<u8>, u64)>> {
-    let cards = verify(
-        many1(map(
-            one_of::<_, _, (&str, ErrorKind)>("AKQJT987654321"),
-            |c| match c {
-                '1' => 0u8,
-                '2' => 1,
-                '3' => 2,
-                '4' => 3,
-                '5' => 4,
-                '6' => 5,
-                '7' => 6,
-                '8' => 7,
-                '9' => 8,
-                'T' => 9,
-                'J' => 10,
-                'Q' => 11,
-                'K' => 12,
-                'A' => 13,
-                _ => unreachable!(),
-            },
-        )),
-        |cards: &Vec<u8>| cards.len() == 5,
-    );
-    let line = separated_pair(cards, space1, nom_u64);
-    let mut parser = terminated(separated_list1(newline, line), multispace0);
-    match parser(input) {
-        Ok((remaining, input)) if remaining == "" => Ok(input),
-        Ok((input, _)) => {
-            println!("Remaining input:");
-            println!("{input:?}");
-            Err(anyhow!("Failed to parse"))
-        }
-        Err(e) => {
-            println!("Failed to parse");
-            println!("{e:?}");
-            Err(anyhow!("Failed to parse"))
-        }
-    }
-}
+mod equity;
+mod hand;
 
-fn rank(hand: &Vec<u8>) -> Result<u8> {
-    let mut hand = hand.clone();
-    hand.sort();
-    let mut unique = hand.clone();
-    unique.dedup();
+pub use equity::{equity, Deck, Equity, Method};
+pub use hand::{Card, Category, Hand, HandType, Ruleset};
 
-    let mut counts = unique
-        .iter()
-        .map(|c1| hand.iter().filter(|c2| c1 == *c2).count())
-        .collect::<Vec<_>>();
-    counts.sort();
-    counts.reverse();
-
-    let rank = if counts[0] == 5 {
-        6
-    } else if counts[0] == 4 {
-        5
-    } else if counts[0] == 3 && counts[1] == 2 {
-        4
-    } else if counts[0] == 3 {
-        3
-    } else if counts[0] == 2 && counts[1] == 2 {
-        2
-    } else if counts[0] == 2 {
-        1
-    } else {
-        0
-    };
-
-    Ok(rank)
-}
-
-pub fn solve1(input: &str) -> Result<u64> {
-    let mut hands = parse(input)?
-        .into_iter()
-        .map(|(hand, bet)| (rank(&hand).unwrap(), hand, bet))
-        .collect::<Vec<_>>();
-
-    hands.sort_by(
-        |(rank1, hand1, _), (rank2, hand2, _)| match rank1.cmp(rank2) {
-            Less => Less,
-            Equal => hand1.cmp(hand2),
-            Greater => Greater,
-        },
-    );
-
-    let result = hands
-        .into_iter()
-        .enumerate()
-        .map(|(i, (_rank, _hand, bet))| bet as u64 * (i as u64 + 1))
-        .reduce(|acc, val| acc + val)
-        .unwrap_or(0);
+pub type Hands = Vec<(Vec<char>, u64)>;
 
-    Ok(result)
+/// The rules each part ranks the hands by.
+pub struct Dec7 {
+    pub part1: Ruleset,
+    pub part2: Ruleset,
 }
 
-fn parse2(input: &str) -> Result<Vec<(Vec<u8>, u64)>> {
-    let cards = verify(
-        many1(map(
-            one_of::<_, _, (&str, ErrorKind)>("AKQJT987654321"),
-            |c| match c {
-                'J' => 0u8,
-                '1' => 1,
-                '2' => 2,
-                '3' => 3,
-                '4' => 4,
-                '5' => 5,
-                '6' => 6,
-                '7' => 7,
-                '8' => 8,
-                '9' => 9,
-                'T' => 10,
-                'Q' => 11,
-                'K' => 12,
-                'A' => 13,
-                _ => unreachable!(),
-            },
-        )),
-        |cards: &Vec<u8>| cards.len() == 5,
-    );
-    let line = separated_pair(cards, space1, nom_u64);
-    let mut parser = terminated(separated_list1(newline, line), multispace0);
-    match parser(input) {
-        Ok((remaining, input)) if remaining == "" => Ok(input),
-        Ok((input, _)) => {
-            println!("Remaining input:");
-            println!("{input:?}");
-            Err(anyhow!("Failed to parse"))
-        }
-        Err(e) => {
-            println!("Failed to parse");
-            println!("{e:?}");
-            Err(anyhow!("Failed to parse"))
+impl Default for Dec7 {
+    fn default() -> Self {
+        Self {
+            part1: Ruleset::standard(),
+            part2: Ruleset::jokers_wild(),
         }
     }
 }
 
-// A better solution is to just make the joker the same of the most frequent
-// card in the hand
-fn rank2(hand: &Vec<u8>) -> Result<u8> {
-    let hand = hand.clone();
-    let (mut jokers, rest) = hand.into_iter().partition::<Vec<_>, _>(|c| *c == 0);
+impl Puzzle for Dec7 {
+    const DAY: u8 = 7;
 
-    if rest.len() == 5 {
-        return rank(&rest);
-    }
+    type Model = Hands;
 
-    let mut max_rank = 0;
+    fn parse(&self, input: &str) -> Result<Hands> {
+        Ok(parse(input, &[&self.part1, &self.part2])?)
+    }
 
-    loop {
+    fn part1(&self, hands: &Hands) -> Result<Answer> {
+        Ok(winnings(hands, &self.part1)?.into())
+    }
 
-        let mut hand = rest.clone();
-        hand.append(&mut jokers.clone());
-        max_rank = max(max_rank, rank(&hand)?);
+    fn part2(&self, hands: &Hands) -> Result<Answer> {
+        Ok(winnings(hands, &self.part2)?.into())
+    }
+}
 
-        if jokers.iter().all(|c| *c == 13) {
-            break;
-        }
+/// Which cards make a valid hand depends on the ruleset, so every hand is
+/// checked against each of `rulesets` once it is parsed.
+fn parse(input: &str, rulesets: &[&Ruleset]) -> Result<Hands, ParseError> {
+    let cards = take_till1::<_, _, VerboseError<_>>(|c: char| c.is_whitespace());
+    let line = context("hand", separated_pair(cards, space1, nom_u64));
+    let hands = parse_all(separated_list1(newline, line), input)?;
 
-        for idx in 0..jokers.len() {
-            if jokers[idx] < 13 {
-                jokers[idx] += 1;
-                break;
-            }
-            else {
-                jokers[idx] = 0;
-            }
+    for (cards, _) in &hands {
+        for ruleset in rulesets {
+            check(input, cards, ruleset)?;
         }
-
     }
+    Ok(hands
+        .into_iter()
+        .map(|(cards, bet)| (cards.chars().collect(), bet))
+        .collect())
+}
 
-    Ok(max_rank)
+/// Point at the first card the ruleset does not know, or at the hand if it
+/// has the wrong number of cards.
+fn check(input: &str, cards: &str, ruleset: &Ruleset) -> Result<(), ParseError> {
+    if let Some((i, _)) = cards
+        .char_indices()
+        .find(|(_, card)| !ruleset.cards.contains(*card))
+    {
+        return Err(ParseError::at(
+            input,
+            &cards[i..],
+            format!("one of the cards {}", ruleset.cards),
+        ));
+    }
+    if cards.chars().count() != ruleset.hand_size {
+        return Err(ParseError::at(
+            input,
+            cards,
+            format!("{} cards in hand", ruleset.hand_size),
+        ));
+    }
+    Ok(())
 }
 
-pub fn solve2(input: &str) -> Result<u64> {
-    let mut hands = parse2(input)?
-        .into_iter()
-        .map(|(hand, bet)| (rank2(&hand).unwrap(), hand, bet))
-        .collect::<Vec<_>>();
-
-    hands.sort_by(
-        |(rank1, hand1, _), (rank2, hand2, _)| match rank1.cmp(rank2) {
-            Less => Less,
-            Equal => hand1.cmp(hand2),
-            Greater => Greater,
-        },
-    );
-
-    let result = hands
+fn winnings(hands: &Hands, ruleset: &Ruleset) -> Result<u64> {
+    let mut hands = hands
+        .iter()
+        .map(|(cards, bet)| Ok((Hand::new(cards, ruleset)?, *bet)))
+        .collect::<Result<Vec<_>>>()?;
+    hands.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    Ok(hands
         .into_iter()
         .enumerate()
-        .map(|(i, (_rank, _hand, bet))| bet as u64 * (i as u64 + 1))
-        .reduce(|acc, val| acc + val)
-        .unwrap_or(0);
+        .map(|(i, (_hand, bet))| bet * (i as u64 + 1))
+        .sum())
+}
 
-    Ok(result)
+pub fn solve1(input: &str) -> Result<u64> {
+    let ruleset = Ruleset::standard();
+    let hands = parse(input, &[&ruleset])?;
+    winnings(&hands, &ruleset)
+}
+
+pub fn solve2(input: &str) -> Result<u64> {
+    let ruleset = Ruleset::jokers_wild();
+    let hands = parse(input, &[&ruleset])?;
+    winnings(&hands, &ruleset)
 }
 
 #[test]
@@ -224,3 +130,15 @@ fn test_example_2() {
     let r = solve2(s);
     assert_eq!(r.unwrap(), 5905);
 }
+
+#[test]
+fn test_parse_error() {
+    let rules = [&Ruleset::standard()];
+    let e = parse("32T3K 765\nKX123 10\n", &rules).unwrap_err();
+    assert_eq!((e.line, e.column), (2, 2));
+    assert_eq!(e.expected, "one of the cards 123456789TJQKA");
+
+    let e = parse("32T3K 765\n32T3 10\n", &rules).unwrap_err();
+    assert_eq!((e.line, e.column), (2, 1));
+    assert_eq!(e.expected, "5 cards in hand");
+}
@@ -0,0 +1,143 @@
+//! A second solver that keys the nodes by their three character labels in a
+//! `BTreeMap`. It is kept to cross-check the main solver.
+
+use std::collections::BTreeMap;
+
+use anyhow::{anyhow, Result};
+use common::{parse_all, ParseError};
+use nom::{
+    bytes::complete::tag,
+    character::complete::{alphanumeric1, multispace1, newline, one_of, space0},
+    combinator::{map, map_res, verify},
+    error::{context, VerboseError},
+    multi::{many1, separated_list1},
+    sequence::{delimited, separated_pair, terminated},
+};
+
+use crate::Direction;
+
+type Label = [u8; 3];
+
+type Nodes = Vec<(Label, (Label, Label))>;
+
+fn parse(input: &str) -> Result<(Vec<Direction>, Nodes), ParseError> {
+    let directions = context(
+        "directions",
+        many1(map(one_of::<_, _, VerboseError<_>>("LR"), |c| {
+            c.try_into().unwrap()
+        })),
+    );
+
+    let label = || {
+        context(
+            "label",
+            map_res(
+                verify(alphanumeric1, |parsed: &str| parsed.len() == 3),
+                |parsed: &str| Label::try_from(parsed.as_bytes()),
+            ),
+        )
+    };
+
+    let payload = delimited(
+        tag("("),
+        separated_pair(label(), terminated(tag(","), space0), label()),
+        tag(")"),
+    );
+    let node = context("node", separated_pair(label(), tag(" = "), payload));
+    let nodes = separated_list1(newline, node);
+
+    parse_all(separated_pair(directions, multispace1, nodes), input)
+}
+
+/// Steps from `start` until `goal` holds, following `directions` from the
+/// beginning. Once there have been more steps than there are pairs of node
+/// and position in the directions, the walk is going round in circles.
+fn walk(
+    tree: &BTreeMap<Label, (Label, Label)>,
+    directions: &[Direction],
+    start: Label,
+    goal: impl Fn(&Label) -> bool,
+) -> Result<u64> {
+    let limit = (tree.len() * directions.len()) as u64;
+    let mut path = start;
+    let mut steps = 0;
+
+    for direction in directions.iter().cycle() {
+        if goal(&path) {
+            break;
+        }
+        if steps > limit {
+            return Err(anyhow!(
+                "No goal is reachable from {}",
+                String::from_utf8_lossy(&start)
+            ));
+        }
+
+        let node = tree
+            .get(&path)
+            .ok_or_else(|| anyhow!("Failed to find node for {}", String::from_utf8_lossy(&path)))?;
+
+        path = match direction {
+            Direction::Left => node.0,
+            Direction::Right => node.1,
+        };
+
+        steps += 1;
+    }
+
+    Ok(steps)
+}
+
+fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+pub fn solve1(input: &str) -> Result<u64> {
+    let (directions, nodes) = parse(input)?;
+    let tree: BTreeMap<_, _> = nodes.into_iter().collect();
+
+    walk(&tree, &directions, *b"AAA", |path| path == b"ZZZ")
+}
+
+/// Assumes every ghost reaches its first goal after exactly one lap of its
+/// cycle, as in the puzzle inputs, so the answer is the LCM of those steps.
+pub fn solve2(input: &str) -> Result<u64> {
+    let (directions, nodes) = parse(input)?;
+    let tree: BTreeMap<_, _> = nodes.into_iter().collect();
+
+    tree.keys()
+        .filter(|src| src[2] == b'A')
+        .map(|src| walk(&tree, &directions, *src, |path| path[2] == b'Z'))
+        .try_fold(1, |acc, steps| {
+            let steps = steps?;
+            Ok(acc / gcd(acc, steps) * steps)
+        })
+}
+
+#[test]
+fn test_backends() {
+    for s in [include_str!("input1.txt"), include_str!("../input.txt")] {
+        assert_eq!(solve1(s).unwrap(), crate::solve1(s).unwrap());
+    }
+    for s in [include_str!("input2.txt"), include_str!("../input.txt")] {
+        assert_eq!(solve2(s).unwrap(), crate::solve2(s).unwrap());
+    }
+}
+
+#[test]
+fn test_unreachable() {
+    let s = "L\n\nAAA = (BBB, BBB)\nBBB = (AAA, ZZZ)\nZZZ = (ZZZ, ZZZ)\n";
+    let e = solve1(s).unwrap_err();
+    assert_eq!(e.to_string(), "No goal is reachable from AAA");
+}
+
+#[test]
+fn test_label_length() {
+    let e = parse("L\n\nAAAA = (BBB, CCC)\n").unwrap_err();
+    assert_eq!((e.line, e.column), (3, 1));
+    assert_eq!(e.expected, "valid value in label");
+}
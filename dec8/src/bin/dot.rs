@@ -0,0 +1,36 @@
+use anyhow::{anyhow, Result};
+use common::Puzzle;
+use dec8::{Dec8, Pattern};
+
+const USAGE: &str =
+    "Usage: dec8-dot [--start <glob>] [--goal <glob>] [--adjacency | instructions] < input";
+
+/// Print the network read from stdin as Graphviz DOT, or as a plain adjacency
+/// list with `--adjacency`. Start and goal nodes, by default those ending in A
+/// and Z, are highlighted. Optional instructions highlight the path taken from
+/// every start node.
+fn main() -> Result<()> {
+    let mut start = Pattern::Suffix("A".into());
+    let mut goal = Pattern::Suffix("Z".into());
+    let mut rest = Vec::new();
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--start" => start = Pattern::Glob(args.next().ok_or_else(|| anyhow!(USAGE))?),
+            "--goal" => goal = Pattern::Glob(args.next().ok_or_else(|| anyhow!(USAGE))?),
+            _ => rest.push(arg),
+        }
+    }
+
+    let input = std::io::read_to_string(std::io::stdin())?;
+    let network = Dec8.parse(&input)?;
+
+    match rest.as_slice() {
+        [flag] if flag == "--adjacency" => print!("{}", network.adjacency()),
+        [] => print!("{}", network.to_dot(&start, &goal, None)?),
+        [instructions] => print!("{}", network.to_dot(&start, &goal, Some(instructions))?),
+        _ => return Err(anyhow!(USAGE)),
+    }
+    Ok(())
+}
@@ -0,0 +1,252 @@
+use std::collections::BTreeSet;
+
+use anyhow::{anyhow, Result};
+
+/// How many congruences `first_common_hit` combines before it gives up.
+const MAX_WORK: usize = 1 << 18;
+
+/// When a walk through the network starts repeating, and the steps at which it
+/// is on a goal node.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Cycle {
+    /// First step of the repeating part.
+    pub start: u64,
+    pub length: u64,
+    /// Goal steps before `start + length`, in order. Those from `start` on
+    /// recur every `length` steps.
+    pub hits: Vec<u64>,
+}
+
+impl Cycle {
+    fn is_hit(&self, step: u64) -> bool {
+        let end = self.start + self.length;
+        let step = if step < end {
+            step
+        } else {
+            self.start + (step - self.start) % self.length
+        };
+        self.hits.binary_search(&step).is_ok()
+    }
+
+    /// The shape of the real input: a single goal, reached again every cycle
+    /// length steps from the start.
+    fn is_clean(&self) -> bool {
+        self.hits == [self.length] && self.start <= self.length
+    }
+}
+
+fn gcd(a: u128, b: u128) -> u128 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+fn lcm(a: u128, b: u128) -> Option<u128> {
+    (a / gcd(a, b)).checked_mul(b)
+}
+
+/// Solve `a * x + b * y = gcd(a, b)`, returning `(gcd, x)`.
+fn extended_gcd(a: i128, b: i128) -> (i128, i128) {
+    let (mut old_r, mut r) = (a, b);
+    let (mut old_s, mut s) = (1, 0);
+    while r != 0 {
+        let q = old_r / r;
+        (old_r, r) = (r, old_r - q * r);
+        (old_s, s) = (s, old_s - q * s);
+    }
+    (old_r, old_s)
+}
+
+fn too_large() -> anyhow::Error {
+    anyhow!("The answer does not fit in u64")
+}
+
+/// Combine `x = a1 (mod m1)` and `x = a2 (mod m2)` into `x = a (mod m)`. The
+/// moduli need not be coprime, but must fit in a `u64`.
+fn crt((a1, m1): (u128, u128), (a2, m2): (u128, u128)) -> Result<Option<(u128, u128)>> {
+    let (g, p) = extended_gcd(m1 as i128, m2 as i128);
+    let diff = a2 as i128 - a1 as i128;
+    if diff % g != 0 {
+        return Ok(None);
+    }
+    let n = m2 / g as u128;
+    let k = ((diff / g).rem_euclid(n as i128) as u128)
+        .checked_mul(p.rem_euclid(n as i128) as u128)
+        .ok_or_else(too_large)?
+        % n;
+    let m = (m1 / g as u128).checked_mul(m2).ok_or_else(too_large)?;
+    let a = m1
+        .checked_mul(k)
+        .and_then(|a| a.checked_add(a1))
+        .ok_or_else(too_large)?;
+    Ok(Some((a, m)))
+}
+
+/// The first step at which every walk is on a goal node, if there is one.
+pub(crate) fn first_common_hit(cycles: &[Cycle]) -> Result<Option<u64>> {
+    const LIMIT: u128 = u64::MAX as u128;
+
+    if cycles.iter().all(Cycle::is_clean) {
+        let steps = cycles
+            .iter()
+            .try_fold(1, |acc, cycle| lcm(acc, cycle.length as u128))
+            .filter(|steps| *steps <= LIMIT)
+            .ok_or_else(too_large)?;
+        return Ok(Some(steps as u64));
+    }
+
+    // Until every walk has settled into its cycle, goals are checked step by
+    // step.
+    let settled = cycles
+        .iter()
+        .map(|cycle| cycle.start + cycle.length)
+        .max()
+        .unwrap_or(0);
+    if let Some(step) = (0..settled).find(|step| cycles.iter().all(|cycle| cycle.is_hit(*step))) {
+        return Ok(Some(step));
+    }
+    let settled = settled as u128;
+
+    // From then on every goal recurs, so every combination of one goal per
+    // walk is a system of congruences. Systems that turn out the same are
+    // kept once. Once the modulus of a system is past `u64::MAX` at most one
+    // of its solutions fits, and that one is checked against the remaining
+    // walks directly.
+    let mut congruences = BTreeSet::from([(0, 1)]);
+    let mut found = BTreeSet::new();
+    let mut overflow = false;
+    let mut work = 0;
+    for cycle in cycles {
+        found.retain(|step| cycle.is_hit(*step as u64));
+        let mut next = BTreeSet::new();
+        for congruence in &congruences {
+            for hit in cycle.hits.iter().filter(|hit| **hit >= cycle.start) {
+                work += 1;
+                if work > MAX_WORK {
+                    return Err(anyhow!("Too many combinations of goals to search"));
+                }
+                let Some((a, m)) = crt(*congruence, (*hit as u128, cycle.length as u128))? else {
+                    continue;
+                };
+                if m <= LIMIT {
+                    next.insert((a, m));
+                } else if settled <= a && a <= LIMIT {
+                    found.insert(a);
+                } else {
+                    overflow = true;
+                }
+            }
+        }
+        congruences = next;
+    }
+
+    for (a, m) in congruences {
+        // The smallest solution that every walk has actually reached.
+        let step = if a >= settled {
+            Some(a)
+        } else {
+            (settled - a)
+                .div_ceil(m)
+                .checked_mul(m)
+                .and_then(|step| step.checked_add(a))
+        };
+        match step.filter(|step| *step <= LIMIT) {
+            Some(step) => {
+                found.insert(step);
+            }
+            None => overflow = true,
+        }
+    }
+
+    // Every step found fits, so any that overflowed would have come later.
+    match found.first() {
+        Some(step) => Ok(Some(*step as u64)),
+        None if overflow => Err(too_large()),
+        None => Ok(None),
+    }
+}
+
+#[test]
+fn test_crt() {
+    assert_eq!(crt((2, 3), (3, 5)).unwrap(), Some((8, 15)));
+    assert_eq!(crt((1, 4), (3, 6)).unwrap(), Some((9, 12)));
+    assert_eq!(crt((1, 4), (2, 6)).unwrap(), None);
+}
+
+#[test]
+fn test_first_common_hit() {
+    let clean = |length| Cycle {
+        start: 1,
+        length,
+        hits: vec![length],
+    };
+    assert_eq!(first_common_hit(&[clean(2), clean(3)]).unwrap(), Some(6));
+
+    // A goal before the cycle starts, and one every 4 steps from step 5 on.
+    let a = Cycle {
+        start: 3,
+        length: 4,
+        hits: vec![1, 5],
+    };
+    let b = Cycle {
+        start: 0,
+        length: 3,
+        hits: vec![1],
+    };
+    assert_eq!(first_common_hit(&[a.clone(), b.clone()]).unwrap(), Some(1));
+    let b = Cycle { hits: vec![2], ..b };
+    assert_eq!(first_common_hit(&[a, b]).unwrap(), Some(5));
+}
+
+#[test]
+fn test_large_answer() {
+    const PRIMES: [u64; 30] = [
+        2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71, 73, 79, 83, 89,
+        97, 101, 103, 107, 109, 113,
+    ];
+    // One step short of a full turn of every loop, so the answer is the
+    // product of the lengths less one.
+    let cycles: Vec<Cycle> = PRIMES
+        .iter()
+        .map(|length| Cycle {
+            start: 0,
+            length: *length,
+            hits: vec![length - 1],
+        })
+        .collect();
+    assert_eq!(
+        first_common_hit(&cycles[..15]).unwrap(),
+        Some(614889782588491409)
+    );
+    let e = first_common_hit(&cycles).unwrap_err();
+    assert_eq!(e.to_string(), "The answer does not fit in u64");
+
+    let clean: Vec<Cycle> = PRIMES
+        .iter()
+        .map(|length| Cycle {
+            start: 1,
+            length: *length,
+            hits: vec![*length],
+        })
+        .collect();
+    assert!(first_common_hit(&clean).is_err());
+}
+
+#[test]
+fn test_many_combinations() {
+    // Ten goals near the end of each loop make a million combinations, too
+    // many to search.
+    let cycles: Vec<Cycle> = [1009, 1013, 1019, 1021, 1031, 1033]
+        .iter()
+        .map(|length| Cycle {
+            start: 0,
+            length: *length,
+            hits: (length - 10..*length).collect(),
+        })
+        .collect();
+    let e = first_common_hit(&cycles).unwrap_err();
+    assert_eq!(e.to_string(), "Too many combinations of goals to search");
+    assert!(first_common_hit(&cycles[..4]).unwrap().is_some());
+}
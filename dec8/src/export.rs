@@ -0,0 +1,116 @@
+use std::collections::BTreeSet;
+use std::fmt::Write;
+
+use anyhow::Result;
+
+use crate::{Direction, Network, Pattern};
+
+impl Network {
+    /// Every node with its left and right neighbours, one per line.
+    pub fn adjacency(&self) -> String {
+        let graph = &self.graph;
+        let mut out = String::new();
+        for node in graph.nodes() {
+            let left = graph.next(node, &Direction::Left);
+            let right = graph.next(node, &Direction::Right);
+            writeln!(
+                out,
+                "{} -> {}, {}",
+                graph.label(node),
+                graph.label(left),
+                graph.label(right)
+            )
+            .unwrap();
+        }
+        out
+    }
+
+    /// The network in Graphviz DOT. Nodes matching `start` and `goal` are
+    /// highlighted. With `instructions`, the edges taken when following them
+    /// once from every start are highlighted too.
+    pub fn to_dot(
+        &self,
+        start: &Pattern,
+        goal: &Pattern,
+        instructions: Option<&str>,
+    ) -> Result<String> {
+        let graph = &self.graph;
+
+        let mut taken = BTreeSet::new();
+        if let Some(instructions) = instructions {
+            let directions = instructions
+                .trim()
+                .chars()
+                .map(Direction::try_from)
+                .collect::<Result<Vec<_>>>()?;
+            for first in graph
+                .nodes()
+                .filter(|node| start.matches(graph.label(*node)))
+            {
+                let mut node = first;
+                for direction in &directions {
+                    taken.insert((node, direction.clone()));
+                    node = graph.next(node, direction);
+                }
+            }
+        }
+
+        let mut out = String::from("digraph network {\n");
+        for node in graph.nodes() {
+            let label = graph.label(node);
+            if start.matches(label) {
+                writeln!(out, "    \"{label}\" [style=filled, fillcolor=palegreen];").unwrap();
+            } else if goal.matches(label) {
+                writeln!(out, "    \"{label}\" [style=filled, fillcolor=lightcoral];").unwrap();
+            }
+        }
+        for node in graph.nodes() {
+            for direction in [Direction::Left, Direction::Right] {
+                let next = graph.next(node, &direction);
+                let name = match direction {
+                    Direction::Left => "L",
+                    Direction::Right => "R",
+                };
+                let style = if taken.contains(&(node, direction)) {
+                    ", color=blue, penwidth=2"
+                } else {
+                    ""
+                };
+                writeln!(
+                    out,
+                    "    \"{}\" -> \"{}\" [label=\"{name}\"{style}];",
+                    graph.label(node),
+                    graph.label(next)
+                )
+                .unwrap();
+            }
+        }
+        out.push_str("}\n");
+
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+use common::Puzzle;
+
+#[test]
+fn test_dot() {
+    let network = crate::Dec8.parse(include_str!("input2.txt")).unwrap();
+    assert!(network.adjacency().starts_with("11A -> 11B, XXX\n"));
+
+    let (start, goal) = (Pattern::Suffix("A".into()), Pattern::Suffix("Z".into()));
+    let dot = network.to_dot(&start, &goal, Some("LR")).unwrap();
+    assert!(dot.contains("\"22A\" [style=filled, fillcolor=palegreen];"));
+    assert!(dot.contains("\"11Z\" [style=filled, fillcolor=lightcoral];"));
+    assert!(dot.contains("\"11B\" -> \"11Z\" [label=\"R\", color=blue, penwidth=2];"));
+    assert!(dot.contains("\"11B\" -> \"XXX\" [label=\"L\"];"));
+
+    assert!(network.to_dot(&start, &goal, Some("LX")).is_err());
+
+    let dot = network
+        .to_dot(&Pattern::Exact("11A".into()), &goal, None)
+        .unwrap();
+    assert!(dot.contains("\"11A\" [style=filled, fillcolor=palegreen];"));
+    assert!(!dot.contains("\"22A\" [style"));
+}
@@ -0,0 +1,77 @@
+use std::collections::HashMap;
+
+use anyhow::{anyhow, Result};
+
+use crate::Direction;
+
+/// The network with nodes interned to dense indices, in the order they are
+/// defined.
+pub(crate) struct Graph {
+    labels: Vec<String>,
+    indices: HashMap<String, u32>,
+    /// Left and right neighbour of each node.
+    edges: Vec<(u32, u32)>,
+}
+
+impl Graph {
+    /// Build the graph from `(label, (left, right))` definitions. Every label
+    /// must be defined exactly once.
+    pub(crate) fn new<'a>(
+        nodes: impl IntoIterator<Item = (&'a str, (&'a str, &'a str))>,
+    ) -> Result<Self> {
+        let nodes: Vec<_> = nodes.into_iter().collect();
+
+        let mut labels = Vec::with_capacity(nodes.len());
+        let mut indices = HashMap::with_capacity(nodes.len());
+        for (src, _) in &nodes {
+            if indices
+                .insert(src.to_string(), labels.len() as u32)
+                .is_some()
+            {
+                return Err(anyhow!("Node {src} is defined more than once"));
+            }
+            labels.push(src.to_string());
+        }
+
+        let index = |src: &str, label: &str| {
+            indices
+                .get(label)
+                .copied()
+                .ok_or_else(|| anyhow!("Node {src} refers to undefined node {label}"))
+        };
+        let edges = nodes
+            .iter()
+            .map(|(src, (left, right))| Ok((index(src, left)?, index(src, right)?)))
+            .collect::<Result<_>>()?;
+
+        Ok(Self {
+            labels,
+            indices,
+            edges,
+        })
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.labels.len()
+    }
+
+    pub(crate) fn node(&self, label: &str) -> Option<u32> {
+        self.indices.get(label).copied()
+    }
+
+    pub(crate) fn label(&self, node: u32) -> &str {
+        &self.labels[node as usize]
+    }
+
+    pub(crate) fn nodes(&self) -> impl Iterator<Item = u32> {
+        0..self.labels.len() as u32
+    }
+
+    pub(crate) fn next(&self, node: u32, direction: &Direction) -> u32 {
+        let (left, right) = self.edges[node as usize];
+        match direction {
+            Direction::Left => left,
+            Direction::Right => right,
+        }
+    }
+}
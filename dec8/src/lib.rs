@@ -1,7 +1,6 @@
-use std::collections::BTreeMap;
-
 #[allow(unused_imports)]
 use anyhow::{anyhow, Result};
+use common::{parse_all, Answer, Puzzle};
 #[allow(unused_imports)]
 use nom::{
     bytes::complete::tag,
@@ -10,13 +9,24 @@ use nom::{
         u64 as nom_u64,
     },
     combinator::{map, verify},
-    error::{Error, ErrorKind},
+    error::{context, Error, ErrorKind, VerboseError},
     multi::{many1, separated_list1},
     sequence::{delimited, separated_pair, terminated},
     IResult,
 };
 
-#[derive(Clone)]
+#[cfg(feature = "alternate")]
+pub mod alternate;
+mod cycle;
+mod export;
+mod graph;
+mod pattern;
+
+use cycle::{first_common_hit, Cycle};
+use graph::Graph;
+pub use pattern::Pattern;
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 enum Direction {
     Left,
     Right,
@@ -34,10 +44,38 @@ impl TryFrom<char> for Direction {
     }
 }
 
-fn parse(input: &str) -> Result<(Vec<Direction>, Vec<(&str, (&str, &str))>)> {
-    let directions = many1(map(one_of::<_, _, Error<_>>("LR"), |c| {
-        c.try_into().unwrap()
-    }));
+pub struct Network {
+    directions: Vec<Direction>,
+    graph: Graph,
+}
+
+pub struct Dec8;
+
+impl Puzzle for Dec8 {
+    const DAY: u8 = 8;
+
+    type Model = Network;
+
+    fn parse(&self, input: &str) -> Result<Network> {
+        parse(input)
+    }
+
+    fn part1(&self, network: &Network) -> Result<Answer> {
+        Ok(steps(network)?.into())
+    }
+
+    fn part2(&self, network: &Network) -> Result<Answer> {
+        Ok(ghost_steps(network)?.into())
+    }
+}
+
+fn parse(input: &str) -> Result<Network> {
+    let directions = context(
+        "directions",
+        many1(map(one_of::<_, _, VerboseError<_>>("LR"), |c| {
+            c.try_into().unwrap()
+        })),
+    );
 
     let key = alphanumeric1;
     let payload = delimited(
@@ -45,129 +83,122 @@ fn parse(input: &str) -> Result<(Vec<Direction>, Vec<(&str, (&str, &str))>)> {
         separated_pair(alphanumeric1, terminated(tag(","), space0), alphanumeric1),
         tag(")"),
     );
-    let node = separated_pair(key, tag(" = "), payload);
+    let node = context("node", separated_pair(key, tag(" = "), payload));
     let nodes = separated_list1(newline, node);
-    let mut parser = terminated(separated_pair(directions, multispace1, nodes), multispace0);
-
-    match parser(input) {
-        Ok((remaining, input)) if remaining == "" => Ok(input),
-        Ok((input, _)) => {
-            println!("Remaining input:");
-            println!("{input:?}");
-            Err(anyhow!("Failed to parse"))
-        }
-        Err(e) => {
-            println!("Failed to parse");
-            println!("{e:?}");
-            Err(anyhow!("Failed to parse"))
-        }
-    }
+
+    let (directions, nodes) = parse_all(separated_pair(directions, multispace1, nodes), input)?;
+
+    let graph = Graph::new(nodes)?;
+
+    Ok(Network { directions, graph })
 }
 
-pub fn solve1(input: &str) -> Result<u64> {
-    let (directions, nodes) = parse(input)?;
-    let tree: BTreeMap<_, _> = nodes.into_iter().collect();
+fn steps(network: &Network) -> Result<u64> {
+    let graph = &network.graph;
+    let start = graph
+        .node("AAA")
+        .ok_or(anyhow!("There is no node AAA to start from"))?;
+    let goal = graph.node("ZZZ");
 
-    let mut path = "AAA";
-    let mut node;
-    let mut steps = 0;
+    let width = network.directions.len();
+    let mut seen = vec![false; graph.len() * width];
+    let mut node = start;
 
-    for direction in directions.into_iter().cycle() {
-        if path == "ZZZ" {
-            break;
+    for step in 0.. {
+        if Some(node) == goal {
+            return Ok(step);
         }
 
-        node = tree
-            .get(path)
-            .ok_or(anyhow!("Failed to find node for {path}"))?;
-
-        path = match direction {
-            Direction::Left => node.0,
-            Direction::Right => node.1,
-        };
+        let instruction = (step % width as u64) as usize;
+        let state = node as usize * width + instruction;
+        if seen[state] {
+            return Err(anyhow!(
+                "ZZZ is unreachable from AAA, the walk repeats after {step} steps"
+            ));
+        }
+        seen[state] = true;
 
-        steps += 1;
+        node = graph.next(node, &network.directions[instruction]);
     }
 
-    Ok(steps)
-}
-
-fn label(input: &str) -> IResult<&str, [u8; 3]> {
-    map(
-        verify(alphanumeric1, |parsed: &str| parsed.len() == 3),
-        |parsed: &str| {
-            parsed
-                .chars()
-                .map(|c| -> u8 { c.try_into().unwrap() })
-                .collect::<Vec<u8>>()
-                .try_into()
-                .unwrap()
-        },
-    )(input)
+    unreachable!("the number of states is finite")
 }
 
-fn parse2(input: &str) -> Result<(Vec<Direction>, Vec<([u8; 3], ([u8; 3], [u8; 3]))>)> {
-    let directions = many1(map(one_of::<_, _, Error<_>>("LR"), |c| {
-        c.try_into().unwrap()
-    }));
+/// Walk from `start` until a (node, instruction) state repeats.
+fn cycle(graph: &Graph, directions: &[Direction], start: u32, goal: impl Fn(u32) -> bool) -> Cycle {
+    let width = directions.len();
+    let mut seen = vec![u64::MAX; graph.len() * width];
+    let mut hits = Vec::new();
+    let mut node = start;
 
-    let payload = delimited(
-        tag("("),
-        separated_pair(label, terminated(tag(","), space0), label),
-        tag(")"),
-    );
-    let node = separated_pair(label, tag(" = "), payload);
-    let nodes = separated_list1(newline, node);
-    let mut parser = terminated(separated_pair(directions, multispace1, nodes), multispace0);
-
-    match parser(input) {
-        Ok((remaining, input)) if remaining == "" => Ok(input),
-        Ok((input, _)) => {
-            println!("Remaining input:");
-            println!("{input:?}");
-            Err(anyhow!("Failed to parse"))
+    for step in 0.. {
+        let instruction = (step % width as u64) as usize;
+        let state = node as usize * width + instruction;
+        if seen[state] != u64::MAX {
+            return Cycle {
+                start: seen[state],
+                length: step - seen[state],
+                hits,
+            };
         }
-        Err(e) => {
-            println!("Failed to parse");
-            println!("{e:?}");
-            Err(anyhow!("Failed to parse"))
+        seen[state] = step;
+        if goal(node) {
+            hits.push(step);
         }
+
+        node = graph.next(node, &directions[instruction]);
     }
+
+    unreachable!("the number of states is finite")
 }
 
-// This should eventually terminate, but did not do so in 30 min for me. Because
-// of the shape partitioned and circular shape of the input, the problem can be
-// reduced to finding the least common multiple of the length from start to
-// finish of each node. This did not occur to me and it is not evident from the
-// problem description.
-pub fn solve2(input: &str) -> Result<u64> {
-    let (directions, nodes) = parse2(input)?;
-    let mut paths = nodes
-        .iter()
-        .filter(|(src, _dst)| src[2] == 'A'.try_into().unwrap())
-        .map(|(src, _dst)| *src)
-        .collect::<Vec<_>>();
-
-    let tree: BTreeMap<_, _> = nodes.into_iter().collect();
-
-    let mut steps = 0;
-    for direction in directions.into_iter().cycle() {
-        if paths.iter().all(|path| path[2] == 'Z'.try_into().unwrap()) {
-            break;
-        }
+impl Network {
+    /// Walk from every node matching `start` at once, and count the steps
+    /// until all walks are on a node matching `goal` at the same time.
+    ///
+    /// Every walk ends up in a cycle, so rather than stepping them together
+    /// the goals of each cycle are combined with LCM, or CRT when the cycles
+    /// are not as neatly shaped as the real input.
+    pub fn steps(&self, start: &Pattern, goal: &Pattern) -> Result<u64> {
+        let graph = &self.graph;
 
-        for path in paths.iter_mut() {
-            let node = tree.get(path).unwrap();
-            *path = match direction {
-                Direction::Left => node.0,
-                Direction::Right => node.1,
+        let mut cycles = Vec::new();
+        for node in graph
+            .nodes()
+            .filter(|node| start.matches(graph.label(*node)))
+        {
+            let cycle = cycle(graph, &self.directions, node, |node| {
+                goal.matches(graph.label(node))
+            });
+            if cycle.hits.is_empty() {
+                return Err(anyhow!(
+                    "The ghost starting at {} never reaches a node {goal}",
+                    graph.label(node)
+                ));
             }
+            cycles.push(cycle);
+        }
+        if cycles.is_empty() {
+            return Err(anyhow!("There is no node {start} to start from"));
         }
 
-        steps += 1;
+        first_common_hit(&cycles)?
+            .ok_or_else(|| anyhow!("The ghosts are never all on goals at the same step"))
     }
+}
 
-    Ok(steps)
+fn ghost_steps(network: &Network) -> Result<u64> {
+    network.steps(&Pattern::Suffix("A".into()), &Pattern::Suffix("Z".into()))
+}
+
+pub fn solve1(input: &str) -> Result<u64> {
+    let network = parse(input)?;
+    steps(&network)
+}
+
+pub fn solve2(input: &str) -> Result<u64> {
+    let network = parse(input)?;
+    ghost_steps(&network)
 }
 
 #[test]
@@ -183,3 +214,70 @@ fn test_example_2() {
     let r = solve2(s);
     assert_eq!(r.unwrap(), 6);
 }
+
+#[test]
+fn test_part_1() {
+    let s = include_str!("../input.txt");
+    let r = solve1(s);
+    assert_eq!(r.unwrap(), 17873);
+}
+
+#[test]
+fn test_part_2() {
+    let s = include_str!("../input.txt");
+    let r = solve2(s);
+    assert_eq!(r.unwrap(), 15746133679061);
+}
+
+#[test]
+fn test_dead_ends() {
+    let r = solve1("L\n\nAAA = (BBB, BBB)\nBBB = (CCC, AAA)\n");
+    assert_eq!(
+        r.unwrap_err().to_string(),
+        "Node BBB refers to undefined node CCC"
+    );
+
+    let r = solve1("L\n\nAAA = (BBB, BBB)\nBBB = (AAA, ZZZ)\nZZZ = (ZZZ, ZZZ)\n");
+    assert_eq!(
+        r.unwrap_err().to_string(),
+        "ZZZ is unreachable from AAA, the walk repeats after 2 steps"
+    );
+
+    let r = solve2("L\n\n11A = (11B, 11B)\n11B = (11A, 11Z)\n11Z = (11Z, 11Z)\n");
+    assert_eq!(
+        r.unwrap_err().to_string(),
+        "The ghost starting at 11A never reaches a node ending in Z"
+    );
+}
+
+#[test]
+fn test_long_labels() {
+    let s = "RL\n\nAAA = (START, ZZZ)\nSTART = (Q1, Q1)\nQ1 = (ZZZ, ZZZ)\nZZZ = (ZZZ, ZZZ)\n";
+    assert_eq!(solve1(s).unwrap(), 1);
+    let s = "L\n\nAAA = (START, ZZZ)\nSTART = (Q1, Q1)\nQ1 = (ZZZ, ZZZ)\nZZZ = (ZZZ, ZZZ)\n";
+    assert_eq!(solve1(s).unwrap(), 3);
+}
+
+#[test]
+fn test_patterns() {
+    let network = parse(include_str!("input2.txt")).unwrap();
+    let steps = network.steps(&Pattern::Glob("2?A".into()), &Pattern::Suffix("Z".into()));
+    assert_eq!(steps.unwrap(), 3);
+    let steps = network.steps(
+        &Pattern::Exact("11A".into()),
+        &Pattern::Prefix("11Z".into()),
+    );
+    assert_eq!(steps.unwrap(), 2);
+
+    let e = network
+        .steps(&Pattern::Exact("AAA".into()), &Pattern::Exact("ZZZ".into()))
+        .unwrap_err();
+    assert_eq!(e.to_string(), "There is no node named AAA to start from");
+}
+
+#[test]
+fn test_every_node_a_goal() {
+    let network = parse(include_str!("../input.txt")).unwrap();
+    let steps = network.steps(&Pattern::Suffix("A".into()), &Pattern::Glob("*".into()));
+    assert_eq!(steps.unwrap(), 0);
+}
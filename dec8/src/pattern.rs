@@ -0,0 +1,61 @@
+use std::fmt;
+
+/// Selects the nodes a walk starts from or heads for, by label.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Pattern {
+    Exact(String),
+    Prefix(String),
+    Suffix(String),
+    /// `?` matches any one character and `*` any run of characters.
+    Glob(String),
+}
+
+impl Pattern {
+    pub fn matches(&self, label: &str) -> bool {
+        match self {
+            Pattern::Exact(exact) => label == exact,
+            Pattern::Prefix(prefix) => label.starts_with(prefix.as_str()),
+            Pattern::Suffix(suffix) => label.ends_with(suffix.as_str()),
+            Pattern::Glob(glob) => {
+                let glob: Vec<char> = glob.chars().collect();
+                let label: Vec<char> = label.chars().collect();
+                glob_matches(&glob, &label)
+            }
+        }
+    }
+}
+
+fn glob_matches(glob: &[char], label: &[char]) -> bool {
+    match (glob.split_first(), label.split_first()) {
+        (None, _) => label.is_empty(),
+        (Some(('*', rest)), _) => {
+            glob_matches(rest, label) || (!label.is_empty() && glob_matches(glob, &label[1..]))
+        }
+        (Some(('?', rest)), Some((_, tail))) => glob_matches(rest, tail),
+        (Some((c, rest)), Some((l, tail))) => c == l && glob_matches(rest, tail),
+        (Some(_), None) => false,
+    }
+}
+
+/// Completes "a node ...".
+impl fmt::Display for Pattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Pattern::Exact(exact) => write!(f, "named {exact}"),
+            Pattern::Prefix(prefix) => write!(f, "starting with {prefix}"),
+            Pattern::Suffix(suffix) => write!(f, "ending in {suffix}"),
+            Pattern::Glob(glob) => write!(f, "matching {glob}"),
+        }
+    }
+}
+
+#[test]
+fn test_matches() {
+    assert!(Pattern::Exact("AAA".into()).matches("AAA"));
+    assert!(!Pattern::Exact("AAA".into()).matches("AAAA"));
+    assert!(Pattern::Prefix("1".into()).matches("11Z"));
+    assert!(Pattern::Suffix("Z".into()).matches("11Z"));
+    assert!(Pattern::Glob("?1*".into()).matches("11Z"));
+    assert!(Pattern::Glob("*Z".into()).matches("Z"));
+    assert!(!Pattern::Glob("?1?".into()).matches("11"));
+}
@@ -1,4 +1,4 @@
-use aoc::solve1 as solve;
+use dec9::solve1 as solve;
 use anyhow::Result;
 
 
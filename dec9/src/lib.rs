@@ -1,5 +1,6 @@
 #[allow(unused_imports)]
 use anyhow::{anyhow, Result};
+use common::{parse_all, Answer, ParseError, Puzzle};
 #[allow(unused_imports)]
 use nom::{
     bytes::complete::tag,
@@ -9,34 +10,40 @@ use nom::{
         i64 as nom_i64,
     },
     combinator::{map, verify},
-    error::{Error, ErrorKind},
+    error::{context, Error, ErrorKind, VerboseError},
     multi::{many1, separated_list1},
     sequence::{delimited, separated_pair, terminated},
     IResult,
 };
 
-#[allow(dead_code)]
-fn parse(input: &str) -> Result<Vec<Vec<i64>>> {
-    let line = separated_list1(space1::<_, Error<_>>, nom_i64);
-    let mut parser = terminated(separated_list1(newline, line), multispace0);
-
-    match parser(input) {
-        Ok((remaining, input)) if remaining == "" => Ok(input),
-        Ok((input, _)) => {
-            println!("Remaining input:");
-            println!("{input:?}");
-            Err(anyhow!("Failed to parse"))
-        }
-        Err(e) => {
-            println!("Failed to parse");
-            println!("{e:?}");
-            Err(anyhow!("Failed to parse"))
-        }
+pub type History = Vec<Vec<i64>>;
+
+pub struct Dec9;
+
+impl Puzzle for Dec9 {
+    const DAY: u8 = 9;
+
+    type Model = History;
+
+    fn parse(&self, input: &str) -> Result<History> {
+        Ok(parse(input)?)
+    }
+
+    fn part1(&self, history: &History) -> Result<Answer> {
+        Ok(extrapolate(history).into())
+    }
+
+    fn part2(&self, history: &History) -> Result<Answer> {
+        Ok(extrapolate_back(history).into())
     }
 }
 
-pub fn solve1(input: &str) -> Result<i64> {
-    let input = parse(input)?;
+fn parse(input: &str) -> Result<History, ParseError> {
+    let line = separated_list1(space1::<_, VerboseError<_>>, nom_i64);
+    parse_all(separated_list1(newline, line), input)
+}
+
+fn extrapolate(input: &History) -> i64 {
     let mut line_results = Vec::new();
 
     for line in input {
@@ -56,11 +63,10 @@ pub fn solve1(input: &str) -> Result<i64> {
         line_results.push(last.into_iter().rev().reduce(|acc,v| acc+v).unwrap());
     }
 
-    Ok(line_results.into_iter().reduce(|acc,v| acc+v).unwrap())
+    line_results.into_iter().reduce(|acc,v| acc+v).unwrap()
 }
 
-pub fn solve2(input: &str) -> Result<i64> {
-    let input = parse(input)?;
+fn extrapolate_back(input: &History) -> i64 {
     let mut line_results = Vec::new();
 
     for line in input {
@@ -80,7 +86,17 @@ pub fn solve2(input: &str) -> Result<i64> {
         line_results.push(last.into_iter().rev().reduce(|acc,v| v - acc).unwrap());
     }
 
-    Ok(line_results.into_iter().reduce(|acc,v| acc+v).unwrap())
+    line_results.into_iter().reduce(|acc,v| acc+v).unwrap()
+}
+
+pub fn solve1(input: &str) -> Result<i64> {
+    let input = parse(input)?;
+    Ok(extrapolate(&input))
+}
+
+pub fn solve2(input: &str) -> Result<i64> {
+    let input = parse(input)?;
+    Ok(extrapolate_back(&input))
 }
 
 #[test]
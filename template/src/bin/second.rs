@@ -1,4 +1,4 @@
-use aoc::solve2 as solve;
+use template::solve2 as solve;
 use anyhow::Result;
 
 
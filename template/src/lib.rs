@@ -1,5 +1,6 @@
 #[allow(unused_imports)]
 use anyhow::{anyhow, Result};
+use common::{parse_all, Answer, ParseError, Puzzle};
 #[allow(unused_imports)]
 use nom::{
     bytes::complete::tag,
@@ -9,39 +10,47 @@ use nom::{
         i64 as nom_i64,
     },
     combinator::{map, verify},
-    error::{Error, ErrorKind},
+    error::{context, Error, ErrorKind, VerboseError},
     multi::{many1, separated_list1},
     sequence::{delimited, separated_pair, terminated},
     IResult,
 };
 
-#[allow(dead_code)]
-fn parse(input: &str) -> Result<()> {
-
-    let parser = alpha1::<_, Error<_>>;
-
-    match parser(input) {
-        Ok((remaining, input)) if remaining == "" => Ok(input),
-        Ok((input, _)) => {
-            println!("Remaining input:");
-            println!("{input:?}");
-            Err(anyhow!("Failed to parse"))
-        }
-        Err(e) => {
-            println!("Failed to parse");
-            println!("{e:?}");
-            Err(anyhow!("Failed to parse"))
-        }
-    }?;
+pub struct Dec;
+
+impl Puzzle for Dec {
+    const DAY: u8 = 0;
+
+    type Model = ();
+
+    fn parse(&self, input: &str) -> Result<()> {
+        Ok(parse(input)?)
+    }
+
+    fn part1(&self, _model: &()) -> Result<Answer> {
+        todo!()
+    }
+
+    fn part2(&self, _model: &()) -> Result<Answer> {
+        todo!()
+    }
+}
+
+fn parse(input: &str) -> Result<(), ParseError> {
+    let parser = alpha1::<_, VerboseError<_>>;
+
+    let _parsed = parse_all(parser, input)?;
 
     todo!()
 }
 
-pub fn solve1(_input: &str) -> Result<u64> {
+pub fn solve1(input: &str) -> Result<u64> {
+    parse(input)?;
     todo!()
 }
 
-pub fn solve2(_input: &str) -> Result<u64> {
+pub fn solve2(input: &str) -> Result<u64> {
+    parse(input)?;
     todo!()
 }
 